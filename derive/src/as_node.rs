@@ -0,0 +1,279 @@
+use crate::attr::{
+	collection_item, doc_comment, option_inner, ContainerAttrs, FieldAttrs, FieldKind, Tag, VariantAttrs,
+};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, spanned::Spanned, Data, DeriveInput, Fields};
+
+pub(crate) fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+	let container = ContainerAttrs::parse(&input.attrs)?;
+	let name = &input.ident;
+
+	let mut bounds = Vec::new();
+	let mut name_arms = Vec::new();
+	let body = match &input.data {
+		Data::Struct(data) => {
+			let access = data.fields.iter().enumerate().map(|(idx, field)| match &field.ident {
+				Some(ident) => quote!(self.#ident),
+				None => {
+					let idx = syn::Index::from(idx);
+					quote!(self.#idx)
+				}
+			});
+			let writes = write_fields(&data.fields, access.collect(), &container, &mut bounds)?;
+			let comment = write_comment(&container, &input.attrs);
+			quote! {
+				#[allow(unused_mut)]
+				let mut node = ::kdlize::builder::Node::default();
+				#comment
+				#(#writes)*
+				node
+			}
+		}
+		Data::Enum(data) => {
+			let mut arms = Vec::with_capacity(data.variants.len());
+			for variant in &data.variants {
+				let attrs = VariantAttrs::parse(variant, &container)?;
+				let ident = &variant.ident;
+				let bindings = (0..variant.fields.len())
+					.map(|idx| format_ident!("__field{idx}"))
+					.collect::<Vec<_>>();
+				let pattern = match &variant.fields {
+					Fields::Named(fields) => {
+						let names = fields.named.iter().map(|field| field.ident.as_ref().unwrap());
+						quote!(Self::#ident { #(#names: #bindings),* })
+					}
+					Fields::Unnamed(_) => quote!(Self::#ident(#(#bindings),*)),
+					Fields::Unit => quote!(Self::#ident),
+				};
+				// Bindings are references, so deref them to access the field like `self.field` would.
+				let access = bindings.iter().map(|binding| quote!((*#binding))).collect();
+				let writes = write_fields(&variant.fields, access, &container, &mut bounds)?;
+				// Variants without their own doc comment use the doc comment of the enum.
+				let comment = match doc_comment(&variant.attrs) {
+					Some(_) => write_comment(&container, &variant.attrs),
+					None => write_comment(&container, &input.attrs),
+				};
+				let variant_name = &attrs.name;
+				let (tag_prefix, tag_suffix) = match &container.tag {
+					Tag::Name => {
+						name_arms.push(quote!(Self::#ident { .. } => #variant_name));
+						(quote!(), quote!())
+					}
+					Tag::Type => (quote!(), quote!(node = node.with_arg_type(#variant_name);)),
+					Tag::Prop(key) => (
+						quote!(node.push(::kdlize::builder::Property(#key, ::kdlize::builder::Value(#variant_name)));),
+						quote!(),
+					),
+				};
+				arms.push(quote! {
+					#pattern => {
+						#[allow(unused_mut)]
+						let mut node = ::kdlize::builder::Node::default();
+						#comment
+						#tag_prefix
+						#(#writes)*
+						#tag_suffix
+						node
+					}
+				});
+			}
+
+			quote! {
+				match self {
+					#(#arms)*
+				}
+			}
+		}
+		Data::Union(_) => {
+			return Err(syn::Error::new(
+				input.span(),
+				"AsKdlNode can only be derived for structs and enums",
+			));
+		}
+	};
+
+	// Enums tagged by node name are written to a node named after the variant.
+	let variant_name = match name_arms.is_empty() {
+		true => quote!(),
+		false => {
+			let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+			quote! {
+				impl #impl_generics #name #ty_generics #where_clause {
+					/// The name of the node this variant is written to.
+					pub fn variant_name(&self) -> &'static str {
+						match self {
+							#(#name_arms,)*
+						}
+					}
+				}
+			}
+		}
+	};
+
+	let mut generics = input.generics.clone();
+	let where_clause = generics.make_where_clause();
+	where_clause.predicates.extend(bounds);
+	let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+	Ok(quote! {
+		impl #impl_generics ::kdlize::AsKdlNode for #name #ty_generics #where_clause {
+			fn as_kdl(&self) -> ::kdlize::builder::Node {
+				#body
+			}
+		}
+
+		#variant_name
+	})
+}
+
+/// Returns the statement which adds the doc comment of the type or variant to the local `node` builder,
+/// if the container opted into `doc_comments`.
+fn write_comment(container: &ContainerAttrs, attrs: &[syn::Attribute]) -> TokenStream {
+	match container.doc_comments.then(|| doc_comment(attrs)).flatten() {
+		Some(doc) => quote!(node.push(::kdlize::builder::Comment(#doc));),
+		None => quote!(),
+	}
+}
+
+/// Returns the statements which push each field into the local `node` builder,
+/// where `access` is the expression for each field's value (e.g. `self.field`).
+fn write_fields(
+	fields: &Fields,
+	access: Vec<TokenStream>,
+	container: &ContainerAttrs,
+	bounds: &mut Vec<syn::WherePredicate>,
+) -> syn::Result<Vec<TokenStream>> {
+	let mut writes = Vec::with_capacity(fields.len());
+	for (field, access) in fields.iter().zip(access) {
+		let attrs = FieldAttrs::parse(field, container)?;
+		let doc = container.doc_comments.then(|| doc_comment(&field.attrs)).flatten();
+		let (write, field_bounds) = write_field(field, &attrs, access, doc)?;
+		writes.push(write);
+		bounds.extend(field_bounds);
+	}
+	Ok(writes)
+}
+
+fn write_field(
+	field: &syn::Field,
+	attrs: &FieldAttrs,
+	access: TokenStream,
+	doc: Option<String>,
+) -> syn::Result<(TokenStream, Vec<syn::WherePredicate>)> {
+	let field_ty = &field.ty;
+
+	if let FieldKind::Children(name) = &attrs.kind {
+		let Some(item) = collection_item(field_ty) else {
+			return Err(syn::Error::new(
+				field_ty.span(),
+				"`children` fields must be a collection type like `Vec<T>`",
+			));
+		};
+		let component = match attrs.value {
+			false => quote!(::kdlize::builder::Children(#name, &#access)),
+			true => quote!(::kdlize::builder::Children(#name, ::kdlize::builder::Value(&#access))),
+		};
+		let component = match attrs.omit_if_empty {
+			false => component,
+			true => quote!(::kdlize::builder::OmitIfEmpty(#component)),
+		};
+		let component = match &doc {
+			None => component,
+			Some(doc) => quote!(::kdlize::builder::Commented(::kdlize::builder::Comment(#doc), #component)),
+		};
+		let bounds = match attrs.value {
+			false => vec![parse_quote!(#item: ::kdlize::AsKdlNode)],
+			true => vec![parse_quote!(#item: ::kdlize::AsKdlValue)],
+		};
+		return Ok((quote!(node.push(#component);), bounds));
+	}
+
+	// Optional properties are skipped by the builder when `None` (null), but optional children need to be unwrapped.
+	let optional_child = match (&attrs.kind, &attrs.default) {
+		(FieldKind::Child(_), None) => option_inner(field_ty),
+		_ => None,
+	};
+	let value = match optional_child {
+		Some(_) => quote!(value),
+		None => quote!(&#access),
+	};
+	let value_ty = optional_child.unwrap_or(field_ty);
+	// Entries can only have inline block comments, but child nodes have line comments before them.
+	let (entry_comment, child_comment) = match &doc {
+		None => (None, None),
+		Some(doc) => (
+			Some(quote!(::kdlize::builder::BlockComment(#doc))),
+			Some(quote!(::kdlize::builder::Comment(#doc))),
+		),
+	};
+	let commented = |comment: Option<TokenStream>, component: TokenStream| match comment {
+		None => component,
+		Some(comment) => quote!(::kdlize::builder::Commented(#comment, #component)),
+	};
+
+	let (component, mut bounds): (TokenStream, Vec<syn::WherePredicate>) = match (&attrs.kind, attrs.value) {
+		(FieldKind::Arg, _) => (
+			commented(entry_comment, quote!(::kdlize::builder::Value(#value))),
+			vec![parse_quote!(#value_ty: ::kdlize::AsKdlValue)],
+		),
+		(FieldKind::Prop(name), _) => (
+			commented(
+				entry_comment,
+				quote!(::kdlize::builder::Property(#name, ::kdlize::builder::Value(#value))),
+			),
+			vec![parse_quote!(#value_ty: ::kdlize::AsKdlValue)],
+		),
+		(FieldKind::Child(name), false) => (
+			{
+				let value = commented(child_comment, value);
+				quote!(::kdlize::builder::Child(#name, #value))
+			},
+			vec![parse_quote!(#value_ty: ::kdlize::AsKdlNode)],
+		),
+		(FieldKind::Child(name), true) => (
+			{
+				let value = commented(child_comment, quote!(::kdlize::builder::Value(#value)));
+				quote!(::kdlize::builder::Child(#name, #value))
+			},
+			vec![parse_quote!(#value_ty: ::kdlize::AsKdlValue)],
+		),
+		(FieldKind::Children(_), _) => unreachable!(),
+	};
+	let component = match attrs.omit_if_empty {
+		false => component,
+		true => quote!(::kdlize::builder::OmitIfEmpty(#component)),
+	};
+
+	let write = match (&attrs.default, optional_child, &attrs.kind) {
+		// Fields equal to their default are omitted, so that reading them back falls back to the same default.
+		(Some(default), _, FieldKind::Arg | FieldKind::Prop(_)) => {
+			let default = default.expr();
+			bounds.push(parse_quote!(#field_ty: ::core::cmp::PartialEq));
+			quote!(node.push(::kdlize::builder::OmitIfEqual(#component, #default));)
+		}
+		(Some(default), _, _) => {
+			let default = default.expr();
+			bounds.push(parse_quote!(#field_ty: ::core::cmp::PartialEq));
+			quote! {
+				if #access != #default {
+					node.push(#component);
+				}
+			}
+		}
+		(None, Some(_), _) => quote! {
+			if let Some(value) = &#access {
+				node.push(#component);
+			}
+		},
+		// Optional arguments are written as `#null` when `None`, so that the arguments after them keep their position.
+		(None, None, FieldKind::Arg) if option_inner(field_ty).is_some() => quote! {
+			match &#access {
+				Some(_) => node.push(#component),
+				None => node.push(::kdlize::kdl::KdlEntry::new(::kdlize::kdl::KdlValue::Null)),
+			}
+		},
+		(None, None, _) => quote!(node.push(#component);),
+	};
+	Ok((write, bounds))
+}
@@ -0,0 +1,309 @@
+use crate::rename::RenameRule;
+use proc_macro2::Span;
+use syn::{spanned::Spanned, Attribute, Field, LitStr};
+
+/// Attributes declared on the deriving type itself, e.g. `#[kdl(context = MyContext)]`.
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+	pub context: Option<syn::Type>,
+	pub rename_all: Option<RenameRule>,
+	/// How the variant of an enum is identified when read from / written to a node.
+	pub tag: Tag,
+	/// The static id of the type, for `NodeId`.
+	pub id: Option<String>,
+	/// Whether `FromKdlNode` errors if the node has entries or children which no field reads.
+	pub deny_unknown_fields: bool,
+	/// Whether `AsKdlNode` writes the doc comments of the type and its fields as KDL comments.
+	pub doc_comments: bool,
+}
+
+/// Where an enum node stores the name of its variant.
+#[derive(Default)]
+pub(crate) enum Tag {
+	/// The name of the node itself, e.g. `circle 5.0`.
+	#[default]
+	Name,
+	/// The type annotation of the first positional argument, e.g. `shape (circle)5.0`.
+	Type,
+	/// A string property, e.g. `shape kind=circle 5.0`.
+	Prop(String),
+}
+
+impl ContainerAttrs {
+	pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+		let mut out = Self::default();
+		for attr in attrs.iter().filter(|attr| attr.path().is_ident("kdl")) {
+			attr.parse_nested_meta(|meta| {
+				if meta.path.is_ident("context") {
+					out.context = Some(meta.value()?.parse()?);
+					return Ok(());
+				}
+				if meta.path.is_ident("tag") {
+					let lit = meta.value()?.parse::<LitStr>()?;
+					out.tag = match lit.value().as_str() {
+						"name" => Tag::Name,
+						"type" => Tag::Type,
+						_ => {
+							return Err(syn::Error::new(
+								lit.span(),
+								"expected `tag = \"name\"` or `tag = \"type\"`",
+							))
+						}
+					};
+					return Ok(());
+				}
+				if meta.path.is_ident("tag_prop") {
+					out.tag = Tag::Prop(meta.value()?.parse::<LitStr>()?.value());
+					return Ok(());
+				}
+				if meta.path.is_ident("id") {
+					out.id = Some(meta.value()?.parse::<LitStr>()?.value());
+					return Ok(());
+				}
+				if meta.path.is_ident("deny_unknown_fields") {
+					out.deny_unknown_fields = true;
+					return Ok(());
+				}
+				if meta.path.is_ident("doc_comments") {
+					out.doc_comments = true;
+					return Ok(());
+				}
+				if meta.path.is_ident("rename_all") {
+					out.rename_all = Some(RenameRule::parse(&meta.value()?.parse()?)?);
+					return Ok(());
+				}
+				Err(meta.error("unsupported kdl container attribute"))
+			})?;
+		}
+		Ok(out)
+	}
+}
+
+/// Where in the node a field is read from / written to.
+pub(crate) enum FieldKind {
+	/// The next positional argument of the node.
+	Arg,
+	/// A named property of the node.
+	Prop(String),
+	/// The first child node with the provided name.
+	Child(String),
+	/// All child nodes with the provided name.
+	Children(String),
+}
+
+pub(crate) enum DefaultValue {
+	/// `Default::default()`
+	Trait,
+	/// A path to a function with no arguments which returns the default value.
+	Path(syn::ExprPath),
+}
+
+impl DefaultValue {
+	/// The expression which evaluates to the default value.
+	pub fn expr(&self) -> proc_macro2::TokenStream {
+		match self {
+			Self::Trait => quote::quote!(::core::default::Default::default()),
+			Self::Path(path) => quote::quote!(#path()),
+		}
+	}
+}
+
+pub(crate) struct FieldAttrs {
+	pub kind: FieldKind,
+	/// If true, `Child` and `Children` fields refer to the first value of the child node(s),
+	/// rather than the child node as a whole.
+	pub value: bool,
+	pub default: Option<DefaultValue>,
+	/// If true, the field is not written when its value is empty (e.g. an empty string or an empty child node).
+	pub omit_if_empty: bool,
+}
+
+impl FieldAttrs {
+	pub fn parse(field: &Field, container: &ContainerAttrs) -> syn::Result<Self> {
+		let ident_name = field.ident.as_ref().map(|ident| {
+			let name = ident.to_string();
+			match container.rename_all {
+				Some(rule) => rule.apply(&name),
+				None => name.trim_start_matches("r#").to_owned(),
+			}
+		});
+		let mut kind = None;
+		let mut value = false;
+		let mut default = None;
+		let mut omit_if_empty = false;
+
+		let mut set_kind = |span: Span, new_kind: FieldKind| {
+			if kind.is_some() {
+				return Err(syn::Error::new(
+					span,
+					"only one of `arg`, `prop`, `child` or `children` may be specified",
+				));
+			}
+			kind = Some(new_kind);
+			Ok(())
+		};
+		let name_or_ident = |meta: &syn::meta::ParseNestedMeta| -> syn::Result<String> {
+			if meta.input.peek(syn::Token![=]) {
+				return Ok(meta.value()?.parse::<LitStr>()?.value());
+			}
+			match &ident_name {
+				Some(name) => Ok(name.clone()),
+				None => Err(meta.error("unnamed fields must provide a name, e.g. `prop = \"name\"`")),
+			}
+		};
+
+		for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("kdl")) {
+			attr.parse_nested_meta(|meta| {
+				let span = meta.path.span();
+				if meta.path.is_ident("arg") {
+					return set_kind(span, FieldKind::Arg);
+				}
+				if meta.path.is_ident("prop") {
+					return set_kind(span, FieldKind::Prop(name_or_ident(&meta)?));
+				}
+				if meta.path.is_ident("child") {
+					return set_kind(span, FieldKind::Child(name_or_ident(&meta)?));
+				}
+				if meta.path.is_ident("children") {
+					return set_kind(span, FieldKind::Children(name_or_ident(&meta)?));
+				}
+				if meta.path.is_ident("value") {
+					value = true;
+					return Ok(());
+				}
+				if meta.path.is_ident("omit_if_empty") {
+					omit_if_empty = true;
+					return Ok(());
+				}
+				if meta.path.is_ident("default") {
+					default = Some(match meta.input.peek(syn::Token![=]) {
+						true => DefaultValue::Path(meta.value()?.parse::<LitStr>()?.parse()?),
+						false => DefaultValue::Trait,
+					});
+					return Ok(());
+				}
+				Err(meta.error("unsupported kdl field attribute"))
+			})?;
+		}
+
+		// Named fields are properties by default, and unnamed (tuple) fields are positional arguments.
+		let kind = match (kind, ident_name) {
+			(Some(kind), _) => kind,
+			(None, Some(name)) => FieldKind::Prop(name),
+			(None, None) => FieldKind::Arg,
+		};
+		if value && !matches!(kind, FieldKind::Child(_) | FieldKind::Children(_)) {
+			return Err(syn::Error::new(
+				field.span(),
+				"`value` is only supported on `child` and `children` fields",
+			));
+		}
+		if default.is_some() && matches!(kind, FieldKind::Children(_)) {
+			return Err(syn::Error::new(
+				field.span(),
+				"`children` fields are always optional and cannot have a `default`",
+			));
+		}
+
+		if default.is_some() && omit_if_empty {
+			return Err(syn::Error::new(
+				field.span(),
+				"`default` fields are already omitted when equal to their default, and cannot be `omit_if_empty`",
+			));
+		}
+
+		Ok(Self {
+			kind,
+			value,
+			default,
+			omit_if_empty,
+		})
+	}
+}
+
+/// Attributes declared on an enum variant, e.g. `#[kdl(rename = "name")]`.
+pub(crate) struct VariantAttrs {
+	/// The name of the variant in KDL, after applying any container `rename_all` rule.
+	pub name: String,
+}
+
+impl VariantAttrs {
+	pub fn parse(variant: &syn::Variant, container: &ContainerAttrs) -> syn::Result<Self> {
+		let mut name = None;
+		for attr in variant.attrs.iter().filter(|attr| attr.path().is_ident("kdl")) {
+			attr.parse_nested_meta(|meta| {
+				if meta.path.is_ident("rename") {
+					name = Some(meta.value()?.parse::<LitStr>()?.value());
+					return Ok(());
+				}
+				Err(meta.error("unsupported kdl variant attribute"))
+			})?;
+		}
+		let name = name.unwrap_or_else(|| {
+			let ident = variant.ident.to_string();
+			match container.rename_all {
+				Some(rule) => rule.apply(&ident),
+				None => ident,
+			}
+		});
+		Ok(Self { name })
+	}
+}
+
+/// Returns the text of the `///` doc comments in a list of attributes, if there are any.
+pub(crate) fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+	let mut lines = Vec::new();
+	for attr in attrs.iter().filter(|attr| attr.path().is_ident("doc")) {
+		let syn::Meta::NameValue(meta) = &attr.meta else {
+			continue;
+		};
+		let syn::Expr::Lit(syn::ExprLit {
+			lit: syn::Lit::Str(lit),
+			..
+		}) = &meta.value
+		else {
+			continue;
+		};
+		let text = lit.value();
+		// `/// text` is stored as ` text`, so drop the space after the slashes.
+		lines.extend(
+			text.lines()
+				.map(|line| line.strip_prefix(' ').unwrap_or(line).trim_end().to_owned()),
+		);
+	}
+	let text = lines.join("\n");
+	let text = text.trim_matches('\n');
+	(!text.is_empty()).then(|| text.to_owned())
+}
+
+/// Returns the inner type if the provided type is `Option<T>`.
+pub(crate) fn option_inner(ty: &syn::Type) -> Option<&syn::Type> {
+	let segment = last_segment(ty)?;
+	if segment.ident != "Option" {
+		return None;
+	}
+	first_generic(segment)
+}
+
+/// Returns the item type of a collection type like `Vec<T>` or `HashSet<T>`.
+pub(crate) fn collection_item(ty: &syn::Type) -> Option<&syn::Type> {
+	first_generic(last_segment(ty)?)
+}
+
+fn last_segment(ty: &syn::Type) -> Option<&syn::PathSegment> {
+	let syn::Type::Path(path) = ty else { return None };
+	if path.qself.is_some() {
+		return None;
+	}
+	path.path.segments.last()
+}
+
+fn first_generic(segment: &syn::PathSegment) -> Option<&syn::Type> {
+	let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+		return None;
+	};
+	args.args.iter().find_map(|arg| match arg {
+		syn::GenericArgument::Type(ty) => Some(ty),
+		_ => None,
+	})
+}
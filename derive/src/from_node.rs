@@ -0,0 +1,284 @@
+use crate::attr::{
+	collection_item, option_inner, ContainerAttrs, DefaultValue, FieldAttrs, FieldKind, Tag, VariantAttrs,
+};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, spanned::Spanned, Data, DeriveInput, Fields};
+
+pub(crate) fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+	let container = ContainerAttrs::parse(&input.attrs)?;
+
+	let mut generics = input.generics.clone();
+	let doc_lifetime = match input.generics.lifetimes().next() {
+		Some(param) => param.lifetime.clone(),
+		None => {
+			let lifetime: syn::Lifetime = parse_quote!('doc);
+			generics.params.insert(0, parse_quote!(#lifetime));
+			lifetime
+		}
+	};
+	let context: syn::Type = match &container.context {
+		Some(context) => context.clone(),
+		None => {
+			generics.params.push(parse_quote!(__Context));
+			parse_quote!(__Context)
+		}
+	};
+
+	let finish = container.deny_unknown_fields.then(|| {
+		quote! {
+			if let Err(err) = node.finish() {
+				node.report(err)?;
+			}
+		}
+	});
+	let mut bounds = Vec::new();
+	let body = match &input.data {
+		Data::Struct(data) => {
+			let (reads, construct) = read_fields(
+				&data.fields,
+				quote!(Self),
+				&container,
+				&doc_lifetime,
+				&context,
+				&mut bounds,
+			)?;
+			quote! {
+				#(#reads)*
+				#finish
+				Ok(#construct)
+			}
+		}
+		Data::Enum(data) => {
+			let type_name = input.ident.to_string();
+			let read_tag = match &container.tag {
+				Tag::Name => quote!(node.name().value()),
+				Tag::Type => quote!(node.arg_type()?),
+				Tag::Prop(key) => quote!({
+					let entry = node.prop(#key)?;
+					::kdlize::reader::EntryExt::to::<&str>(entry).map_err(|err| err.with_path(node.entry_path(entry)))?
+				}),
+			};
+			let mut names = Vec::with_capacity(data.variants.len());
+			let mut arms = Vec::with_capacity(data.variants.len());
+			for variant in &data.variants {
+				let attrs = VariantAttrs::parse(variant, &container)?;
+				let ident = &variant.ident;
+				let (reads, construct) = read_fields(
+					&variant.fields,
+					quote!(Self::#ident),
+					&container,
+					&doc_lifetime,
+					&context,
+					&mut bounds,
+				)?;
+				let name = &attrs.name;
+				arms.push(quote! {
+					#name => {
+						#(#reads)*
+						#finish
+						Ok(#construct)
+					}
+				});
+				names.push(attrs.name);
+			}
+			quote! {
+				const VARIANTS: &[&str] = &[#(#names),*];
+				let tag = #read_tag;
+				match tag {
+					#(#arms)*
+					_ => Err(::kdlize::error::UnknownVariant {
+						type_name: #type_name,
+						value: tag.to_owned(),
+						expected: VARIANTS,
+					}
+					.into()),
+				}
+			}
+		}
+		Data::Union(_) => {
+			return Err(syn::Error::new(
+				input.span(),
+				"FromKdlNode can only be derived for structs and enums",
+			));
+		}
+	};
+
+	let where_clause = generics.make_where_clause();
+	where_clause.predicates.extend(bounds);
+	let (impl_generics, _, where_clause) = generics.split_for_impl();
+	let (_, ty_generics, _) = input.generics.split_for_impl();
+	let name = &input.ident;
+
+	Ok(quote! {
+		impl #impl_generics ::kdlize::FromKdlNode<#doc_lifetime, #context> for #name #ty_generics #where_clause {
+			type Error = ::kdlize::MacroReport;
+			fn from_kdl(node: &mut ::kdlize::reader::Node<#doc_lifetime, #context>) -> Result<Self, Self::Error> {
+				#body
+			}
+		}
+	})
+}
+
+/// Returns the statements which read each field into a local, and the expression which constructs
+/// the struct or variant at `path` from those locals.
+fn read_fields(
+	fields: &Fields,
+	path: TokenStream,
+	container: &ContainerAttrs,
+	doc: &syn::Lifetime,
+	context: &syn::Type,
+	bounds: &mut Vec<syn::WherePredicate>,
+) -> syn::Result<(Vec<TokenStream>, TokenStream)> {
+	let mut reads = Vec::new();
+	let mut idents = Vec::new();
+	for (idx, field) in fields.iter().enumerate() {
+		let attrs = FieldAttrs::parse(field, container)?;
+		let ident = format_ident!("__field{idx}");
+		let (read, field_bounds) = read_field(field, &attrs, doc, context)?;
+		let field_ty = &field.ty;
+		// Each field is read in its own closure so that, if the reader has a diagnostics sink,
+		// a failed field is recorded and the remaining fields are still read.
+		reads.push(quote! {
+			#[allow(clippy::redundant_closure_call, clippy::needless_question_mark)]
+			let #ident = match (|| -> Result<#field_ty, ::kdlize::MacroReport> { Ok(#read) })() {
+				Ok(value) => Some(value),
+				Err(err) => {
+					node.report(err)?;
+					None
+				}
+			};
+		});
+		bounds.extend(field_bounds);
+		idents.push(ident);
+	}
+	if !idents.is_empty() {
+		reads.push(quote! {
+			let (#(Some(#idents),)*) = (#(#idents,)*) else {
+				return Err(::kdlize::error::Reported.into());
+			};
+		});
+	}
+
+	let construct = match fields {
+		Fields::Named(fields) => {
+			let names = fields.named.iter().map(|field| field.ident.as_ref().unwrap());
+			quote!(#path { #(#names: #idents),* })
+		}
+		Fields::Unnamed(_) => quote!(#path(#(#idents),*)),
+		Fields::Unit => path,
+	};
+	Ok((reads, construct))
+}
+
+/// Returns the expression which reads the field, and the bounds required by the field type.
+fn read_field(
+	field: &syn::Field,
+	attrs: &FieldAttrs,
+	doc: &syn::Lifetime,
+	context: &syn::Type,
+) -> syn::Result<(TokenStream, Vec<syn::WherePredicate>)> {
+	let field_ty = &field.ty;
+
+	if let FieldKind::Children(name) = &attrs.kind {
+		let Some(item) = collection_item(field_ty) else {
+			return Err(syn::Error::new(
+				field_ty.span(),
+				"`children` fields must be a collection type like `Vec<T>`",
+			));
+		};
+		return Ok(match attrs.value {
+			false => (
+				quote!(node.collect_all::<_, _, #field_ty>(node.children(#name).to::<#item>())?),
+				node_bounds(item, doc, context),
+			),
+			true => (
+				quote!(node.collect_all::<_, _, #field_ty>(node.children(#name).value().to::<#item>().map(
+					|item| -> Result<#item, ::kdlize::MacroReport> { Ok(item??) }
+				))?),
+				value_bounds(item, doc),
+			),
+		});
+	}
+
+	// Fields which are omitted when empty are written by AsKdlNode as absent, so read them back as empty.
+	let default = match (&attrs.default, attrs.omit_if_empty) {
+		(None, true) if option_inner(field_ty).is_none() => Some(&DefaultValue::Trait),
+		(default, _) => default.as_ref(),
+	};
+
+	// Optional fields are read as `None` when absent, otherwise absent fields fallback to their default (if any).
+	let optional = match default {
+		Some(_) => None,
+		None => option_inner(field_ty),
+	};
+	let value_ty = optional.unwrap_or(field_ty);
+
+	let (lookup, convert, bounds) = match (&attrs.kind, attrs.value) {
+		(FieldKind::Arg, _) => (
+			quote!(node.next()),
+			quote!(::kdlize::reader::EntryExt::to::<#value_ty>(found).map_err(|err| err.with_path(node.entry_path(found)))?),
+			value_bounds(value_ty, doc),
+		),
+		(FieldKind::Prop(name), _) => (
+			quote!(node.prop(#name)),
+			quote!(::kdlize::reader::EntryExt::to::<#value_ty>(found).map_err(|err| err.with_path(node.entry_path(found)))?),
+			value_bounds(value_ty, doc),
+		),
+		(FieldKind::Child(name), false) => (
+			quote!(node.child(#name)),
+			quote!({
+				let mut found = found;
+				found.to::<#value_ty>()?
+			}),
+			node_bounds(value_ty, doc, context),
+		),
+		(FieldKind::Child(name), true) => (
+			quote!(node.child(#name)),
+			quote!({
+				let mut found = found;
+				let entry = found.next()?;
+				::kdlize::reader::EntryExt::to::<#value_ty>(entry).map_err(|err| err.with_path(found.entry_path(entry)))?
+			}),
+			value_bounds(value_ty, doc),
+		),
+		(FieldKind::Children(_), _) => unreachable!(),
+	};
+
+	let (convert, fallback) = match (default, optional) {
+		(Some(default), _) => (convert, default.expr()),
+		// Optional entries written as `None` are `#null`, to keep the position of the arguments after them.
+		(None, Some(_)) if matches!(attrs.kind, FieldKind::Arg | FieldKind::Prop(_)) => (
+			quote!(match found.value().is_null() {
+				true => None,
+				false => Some(#convert),
+			}),
+			quote!(None),
+		),
+		(None, Some(_)) => (quote!(Some(#convert)), quote!(None)),
+		(None, None) => (convert, quote!(return Err(err.into()))),
+	};
+
+	let read = quote! {
+		match #lookup {
+			Ok(found) => #convert,
+			#[allow(unused_variables)]
+			Err(err) => #fallback,
+		}
+	};
+	Ok((read, bounds))
+}
+
+fn value_bounds(ty: &syn::Type, doc: &syn::Lifetime) -> Vec<syn::WherePredicate> {
+	vec![
+		parse_quote!(#ty: ::kdlize::FromKdlValue<#doc>),
+		parse_quote!(::kdlize::MacroReport: From<<#ty as ::kdlize::FromKdlValue<#doc>>::Error>),
+	]
+}
+
+fn node_bounds(ty: &syn::Type, doc: &syn::Lifetime, context: &syn::Type) -> Vec<syn::WherePredicate> {
+	vec![
+		parse_quote!(#ty: ::kdlize::FromKdlNode<#doc, #context>),
+		parse_quote!(::kdlize::MacroReport: From<<#ty as ::kdlize::FromKdlNode<#doc, #context>>::Error>),
+	]
+}
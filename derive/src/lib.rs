@@ -0,0 +1,107 @@
+//! Derive macros for the traits in [kdlize](https://docs.rs/kdlize).
+//!
+//! These are re-exported by `kdlize` when its `derive` feature is enabled (which it is by default),
+//! and should be used through that crate.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod as_node;
+mod attr;
+mod from_node;
+mod node_id;
+mod rename;
+mod value_enum;
+
+/// Implements `kdlize::FromKdlNode` for a struct or enum, reading each field from the node via `kdlize::reader::Node`.
+///
+/// Each field is read according to its `#[kdl(...)]` attribute:
+/// - `arg`: the next positional argument of the node (default for tuple fields)
+/// - `prop` / `prop = "name"`: a property of the node (default for named fields)
+/// - `child` / `child = "name"`: the first child node with the name, parsed via `FromKdlNode`
+/// - `children` / `children = "name"`: all child nodes with the name, collected into the field's collection type
+///
+/// `child` and `children` may also specify `value`, in which case the first argument of the child node(s)
+/// is parsed via `FromKdlValue` instead of the whole node.
+///
+/// Fields of type `Option<T>` are `None` when absent or `#null`. Fields marked `default` or `default = "path::to::fn"`
+/// fallback to `Default::default()` or the result of the function when absent, as do `omit_if_empty` fields.
+///
+/// The impl is generic over the reader's context unless one is specified with `#[kdl(context = Type)]` on the struct.
+/// Names derived from field identifiers can be converted with `#[kdl(rename_all = "kebab-case")]` on the struct.
+/// With `#[kdl(deny_unknown_fields)]`, any arguments, properties or children which are not read by a field
+/// are reported as an error (see `reader::Node::finish`).
+/// If the reader has a diagnostics sink (see `reader::Diagnostics`), fields which fail to read are recorded
+/// to it and the remaining fields are still read, so that every error in a document is reported at once.
+///
+/// For enums, the fields of the selected variant are read as above. The variant is selected by its name
+/// (see `FromKdlValue` for naming attributes), which is stored according to the enum's tagging strategy:
+/// - `#[kdl(tag = "name")]` (default): the name of the node, e.g. `circle 5.0`
+/// - `#[kdl(tag = "type")]`: the type annotation of the first positional argument, e.g. `shape (circle)5.0`,
+///   which replaces the argument's own annotation (variants without arguments are written `shape (empty)#null`)
+/// - `#[kdl(tag_prop = "kind")]`: a string property, e.g. `shape kind=circle 5.0`
+#[proc_macro_derive(FromKdlNode, attributes(kdl))]
+pub fn derive_from_kdl_node(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	from_node::derive(input)
+		.unwrap_or_else(syn::Error::into_compile_error)
+		.into()
+}
+
+/// Implements `kdlize::AsKdlNode` for a struct or enum, writing each field into a `kdlize::builder::Node`.
+///
+/// Fields use the same `#[kdl(...)]` attributes as `FromKdlNode`, so that a value written by this impl
+/// is read back to the same value:
+/// - `Option<T>` fields are skipped when `None`, except positional arguments which are written as `#null`
+/// - `default` fields are skipped (via `OmitIfEqual` for entries) when equal to their default value
+/// - `omit_if_empty` fields are wrapped in `OmitIfEmpty`
+///
+/// With `#[kdl(doc_comments)]` on the type, its doc comments are written as KDL comments: the type's (or variant's)
+/// before the node, a field's before its child node(s), and a field's inline before its argument or property.
+///
+/// Enums write the variant name using the same tagging strategy as `FromKdlNode`. Since a builder cannot name
+/// its own node, enums tagged by node name get a `variant_name` method, which is the name to build them with.
+#[proc_macro_derive(AsKdlNode, attributes(kdl))]
+pub fn derive_as_kdl_node(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	as_node::derive(input)
+		.unwrap_or_else(syn::Error::into_compile_error)
+		.into()
+}
+
+/// Implements `kdlize::FromKdlValue` for an enum without fields, parsing each variant from a string value.
+///
+/// Variants are named by their identifier, unless renamed with `#[kdl(rename = "name")]` on the variant
+/// or `#[kdl(rename_all = "...")]` on the enum. Supported rules are `lowercase`, `UPPERCASE`, `PascalCase`,
+/// `camelCase`, `snake_case`, `SCREAMING_SNAKE_CASE`, `kebab-case` and `SCREAMING-KEBAB-CASE`.
+///
+/// Strings which do not match a variant fail with `kdlize::error::UnknownVariant`, listing the valid names.
+#[proc_macro_derive(FromKdlValue, attributes(kdl))]
+pub fn derive_from_kdl_value(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	value_enum::derive_from_value(input)
+		.unwrap_or_else(syn::Error::into_compile_error)
+		.into()
+}
+
+/// Implements `kdlize::AsKdlValue` for an enum without fields, writing each variant as a string value.
+///
+/// Uses the same naming attributes as `FromKdlValue`.
+#[proc_macro_derive(AsKdlValue, attributes(kdl))]
+pub fn derive_as_kdl_value(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	value_enum::derive_as_value(input)
+		.unwrap_or_else(syn::Error::into_compile_error)
+		.into()
+}
+
+/// Implements `kdlize::NodeId` for a type, replacing `kdlize::impl_kdl_node!`.
+///
+/// The id is the kebab-cased name of the type, unless specified with `#[kdl(id = "...")]`.
+#[proc_macro_derive(NodeId, attributes(kdl))]
+pub fn derive_node_id(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	node_id::derive(input)
+		.unwrap_or_else(syn::Error::into_compile_error)
+		.into()
+}
@@ -0,0 +1,26 @@
+use crate::{attr::ContainerAttrs, rename::RenameRule};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::DeriveInput;
+
+pub(crate) fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+	let container = ContainerAttrs::parse(&input.attrs)?;
+	let name = &input.ident;
+	let id = match &container.id {
+		Some(id) => id.clone(),
+		None => RenameRule::Kebab.apply(&name.to_string()),
+	};
+
+	let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+	Ok(quote! {
+		impl #impl_generics ::kdlize::NodeId for #name #ty_generics #where_clause {
+			fn id() -> &'static str {
+				#id
+			}
+
+			fn get_id(&self) -> &'static str {
+				#id
+			}
+		}
+	})
+}
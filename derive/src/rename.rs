@@ -0,0 +1,115 @@
+/// Case conversion applied to the names of fields or variants, via `#[kdl(rename_all = "...")]`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum RenameRule {
+	Lower,
+	Upper,
+	Pascal,
+	Camel,
+	Snake,
+	ScreamingSnake,
+	Kebab,
+	ScreamingKebab,
+}
+
+impl RenameRule {
+	pub const ALL: &'static [(&'static str, Self)] = &[
+		("lowercase", Self::Lower),
+		("UPPERCASE", Self::Upper),
+		("PascalCase", Self::Pascal),
+		("camelCase", Self::Camel),
+		("snake_case", Self::Snake),
+		("SCREAMING_SNAKE_CASE", Self::ScreamingSnake),
+		("kebab-case", Self::Kebab),
+		("SCREAMING-KEBAB-CASE", Self::ScreamingKebab),
+	];
+
+	pub fn parse(lit: &syn::LitStr) -> syn::Result<Self> {
+		let value = lit.value();
+		for (name, rule) in Self::ALL {
+			if *name == value {
+				return Ok(*rule);
+			}
+		}
+		let names = Self::ALL.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+		Err(syn::Error::new(
+			lit.span(),
+			format!("unknown rename rule {value:?}, expected one of: {}", names.join(", ")),
+		))
+	}
+
+	/// Applies the rule to an identifier in either `PascalCase` (types, variants) or `snake_case` (fields).
+	pub fn apply(self, ident: &str) -> String {
+		let words = split_words(ident);
+		match self {
+			Self::Lower => words.concat(),
+			Self::Upper => words.concat().to_uppercase(),
+			Self::Pascal => words.iter().map(|word| capitalize(word)).collect(),
+			Self::Camel => {
+				let mut words = words.iter();
+				let first = words.next().cloned().unwrap_or_default();
+				first + &words.map(|word| capitalize(word)).collect::<String>()
+			}
+			Self::Snake => words.join("_"),
+			Self::ScreamingSnake => words.join("_").to_uppercase(),
+			Self::Kebab => words.join("-"),
+			Self::ScreamingKebab => words.join("-").to_uppercase(),
+		}
+	}
+}
+
+/// Splits an identifier into lowercase words, at underscores and at the start of each uppercase run.
+fn split_words(ident: &str) -> Vec<String> {
+	let mut words = Vec::new();
+	let mut current = String::new();
+	let chars = ident.trim_start_matches("r#").chars().collect::<Vec<_>>();
+	for (idx, ch) in chars.iter().enumerate() {
+		if *ch == '_' {
+			if !current.is_empty() {
+				words.push(std::mem::take(&mut current));
+			}
+			continue;
+		}
+		if ch.is_uppercase() && !current.is_empty() {
+			let prev_upper = chars[idx - 1].is_uppercase();
+			let next_lower = chars.get(idx + 1).is_some_and(|next| next.is_lowercase());
+			// `HTTPServer` splits into `http` and `server`
+			if !prev_upper || next_lower {
+				words.push(std::mem::take(&mut current));
+			}
+		}
+		current.extend(ch.to_lowercase());
+	}
+	if !current.is_empty() {
+		words.push(current);
+	}
+	words
+}
+
+fn capitalize(word: &str) -> String {
+	let mut chars = word.chars();
+	match chars.next() {
+		Some(first) => first.to_uppercase().chain(chars).collect(),
+		None => String::new(),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn variants() {
+		assert_eq!(RenameRule::Kebab.apply("FastMode"), "fast-mode");
+		assert_eq!(RenameRule::Lower.apply("FastMode"), "fastmode");
+		assert_eq!(RenameRule::Snake.apply("HTTPServer"), "http_server");
+		assert_eq!(RenameRule::Camel.apply("FastMode"), "fastMode");
+		assert_eq!(RenameRule::ScreamingKebab.apply("FastMode"), "FAST-MODE");
+	}
+
+	#[test]
+	fn fields() {
+		assert_eq!(RenameRule::Kebab.apply("max_connections"), "max-connections");
+		assert_eq!(RenameRule::Pascal.apply("max_connections"), "MaxConnections");
+		assert_eq!(RenameRule::Upper.apply("port"), "PORT");
+	}
+}
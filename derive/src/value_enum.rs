@@ -0,0 +1,77 @@
+use crate::attr::{ContainerAttrs, VariantAttrs};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{spanned::Spanned, Data, DeriveInput, Fields};
+
+/// The KDL names of each variant of a fieldless enum, paired with the variant identifier.
+fn variants(input: &DeriveInput, derive_name: &str) -> syn::Result<Vec<(String, syn::Ident)>> {
+	let container = ContainerAttrs::parse(&input.attrs)?;
+	let Data::Enum(data) = &input.data else {
+		return Err(syn::Error::new(
+			input.span(),
+			format!("{derive_name} can only be derived for enums without fields"),
+		));
+	};
+	let mut variants = Vec::with_capacity(data.variants.len());
+	for variant in &data.variants {
+		if !matches!(variant.fields, Fields::Unit) {
+			return Err(syn::Error::new(
+				variant.span(),
+				format!("{derive_name} can only be derived for enums without fields"),
+			));
+		}
+		let attrs = VariantAttrs::parse(variant, &container)?;
+		if let Some((_, existing)) = variants.iter().find(|(name, _)| *name == attrs.name) {
+			return Err(syn::Error::new(
+				variant.span(),
+				format!("variant has the same name {:?} as {existing}", attrs.name),
+			));
+		}
+		variants.push((attrs.name, variant.ident.clone()));
+	}
+	Ok(variants)
+}
+
+pub(crate) fn derive_from_value(input: DeriveInput) -> syn::Result<TokenStream> {
+	let variants = variants(&input, "FromKdlValue")?;
+	let names = variants.iter().map(|(name, _)| name);
+	let idents = variants.iter().map(|(_, ident)| ident);
+	let all_names = variants.iter().map(|(name, _)| name);
+	let name = &input.ident;
+	let type_name = name.to_string();
+	Ok(quote! {
+		impl<'doc> ::kdlize::FromKdlValue<'doc> for #name {
+			type Error = ::kdlize::error::InvalidVariant;
+			fn from_kdl(value: &'doc ::kdlize::kdl::KdlValue) -> Result<Self, Self::Error> {
+				const VARIANTS: &[&str] = &[#(#all_names),*];
+				let value = <&str as ::kdlize::FromKdlValue>::from_kdl(value)?;
+				match value {
+					#(#names => Ok(Self::#idents),)*
+					_ => Err(::kdlize::error::UnknownVariant {
+						type_name: #type_name,
+						value: value.to_owned(),
+						expected: VARIANTS,
+					}
+					.into()),
+				}
+			}
+		}
+	})
+}
+
+pub(crate) fn derive_as_value(input: DeriveInput) -> syn::Result<TokenStream> {
+	let variants = variants(&input, "AsKdlValue")?;
+	let names = variants.iter().map(|(name, _)| name);
+	let idents = variants.iter().map(|(_, ident)| ident);
+	let name = &input.ident;
+	Ok(quote! {
+		impl ::kdlize::AsKdlValue for #name {
+			fn as_kdl(&self) -> ::kdlize::kdl::KdlValue {
+				let name = match self {
+					#(Self::#idents => #names,)*
+				};
+				::kdlize::kdl::KdlValue::String(name.to_owned())
+			}
+		}
+	})
+}
@@ -0,0 +1,174 @@
+use kdlize::{reader::Node, AsKdlNode, FromKdlNode};
+
+#[derive(FromKdlNode, AsKdlNode, Debug, PartialEq, Clone)]
+struct Tls {
+	#[kdl(arg)]
+	cert: String,
+	key: Option<String>,
+}
+
+#[derive(FromKdlNode, AsKdlNode, Debug, PartialEq, Clone)]
+struct Route(String, #[kdl(prop = "to")] String);
+
+#[derive(FromKdlNode, AsKdlNode, Debug, PartialEq, Clone)]
+struct Server {
+	#[kdl(arg)]
+	name: String,
+	port: u16,
+	#[kdl(prop = "max-connections", default)]
+	max_connections: u32,
+	#[kdl(prop, default = "default_timeout")]
+	timeout: u32,
+	#[kdl(prop, omit_if_empty)]
+	description: String,
+	#[kdl(child)]
+	tls: Option<Tls>,
+	#[kdl(children = "route")]
+	routes: Vec<Route>,
+	#[kdl(child = "owner", value)]
+	owner: String,
+	#[kdl(children = "tag", value)]
+	tags: Vec<String>,
+}
+
+fn default_timeout() -> u32 {
+	30
+}
+
+fn server() -> Server {
+	Server {
+		name: "main".into(),
+		port: 8080,
+		max_connections: 0,
+		timeout: 10,
+		description: String::new(),
+		tls: Some(Tls {
+			cert: "cert.pem".into(),
+			key: None,
+		}),
+		routes: vec![Route("/".into(), "index".into())],
+		owner: "ops".into(),
+		tags: vec!["a".into(), "b".into()],
+	}
+}
+
+#[test]
+fn struct_all_kinds() {
+	let node = server().as_kdl().build("server");
+	let expected = "server main port=8080 timeout=10 {\n    tls cert.pem\n    route \"/\" to=index\n    owner ops\n    tag a\n    tag b\n}\n";
+	assert_eq!(node.to_string(), expected);
+}
+
+#[test]
+fn omit_optional_and_defaults() {
+	let server = Server {
+		timeout: 30,
+		tls: None,
+		routes: Vec::new(),
+		tags: Vec::new(),
+		..server()
+	};
+	let node = server.as_kdl().build("server");
+	assert_eq!(node.to_string(), "server main port=8080 {\n    owner ops\n}\n");
+}
+
+#[test]
+fn round_trip() -> miette::Result<()> {
+	for value in [
+		server(),
+		Server {
+			max_connections: 5,
+			description: "primary".into(),
+			tls: None,
+			..server()
+		},
+	] {
+		let node = value.as_kdl().build("server");
+		let parsed = node.to_string().parse::<kdl::KdlDocument>().expect("valid kdl");
+		let read = Node::new(&parsed.nodes()[0], &()).to::<Server>()?;
+		assert_eq!(read, value);
+	}
+	Ok(())
+}
+
+#[test]
+fn generic_field() {
+	#[derive(AsKdlNode)]
+	struct Wrapper<T> {
+		#[kdl(arg)]
+		value: T,
+	}
+	let node = Wrapper { value: 42 }.as_kdl().build("node");
+	assert_eq!(node.to_string(), "node 42\n");
+}
+
+#[test]
+fn doc_comments() -> miette::Result<()> {
+	/// An http server
+	#[derive(AsKdlNode, FromKdlNode, Debug, PartialEq)]
+	#[kdl(doc_comments)]
+	struct Documented {
+		/// The name of the server
+		#[kdl(arg)]
+		name: String,
+		/// The port to listen on
+		#[kdl(default)]
+		port: u16,
+		/// Whether to use tls,
+		/// if it is available.
+		#[kdl(child, value)]
+		tls: Option<bool>,
+		/// The users allowed to connect
+		#[kdl(children = "user", value)]
+		users: Vec<String>,
+		// Not a doc comment
+		#[kdl(child, value)]
+		owner: String,
+	}
+	let value = Documented {
+		name: "main".into(),
+		port: 80,
+		tls: Some(true),
+		users: vec!["a".into(), "b".into()],
+		owner: "ops".into(),
+	};
+	let node = value.as_kdl().build("server");
+	let expected = "// An http server
+server /* The name of the server */ main /* The port to listen on */ port=80 {
+    // Whether to use tls,
+    // if it is available.
+    tls #true
+    // The users allowed to connect
+    user a
+    user b
+    owner ops
+}
+";
+	assert_eq!(node.to_string(), expected);
+
+	let parsed = node.to_string().parse::<kdl::KdlDocument>().expect("valid kdl");
+	let read = Node::new(&parsed.nodes()[0], &()).to::<Documented>()?;
+	assert_eq!(read, value);
+	Ok(())
+}
+
+#[derive(FromKdlNode, AsKdlNode, Debug, PartialEq)]
+struct Pair {
+	#[kdl(arg)]
+	a: Option<u32>,
+	#[kdl(arg)]
+	b: u32,
+}
+
+#[test]
+fn optional_arg_round_trip() -> miette::Result<()> {
+	for value in [Pair { a: None, b: 5 }, Pair { a: Some(1), b: 5 }] {
+		let node = value.as_kdl().build("pair");
+		let parsed = node.to_string().parse::<kdl::KdlDocument>().expect("valid kdl");
+		let read = Node::new(&parsed.nodes()[0], &()).to::<Pair>()?;
+		assert_eq!(read, value);
+	}
+	let node = Pair { a: None, b: 5 }.as_kdl().build("pair");
+	assert_eq!(node.to_string(), "pair #null 5\n");
+	Ok(())
+}
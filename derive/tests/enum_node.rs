@@ -0,0 +1,144 @@
+use kdlize::{builder, reader::Node, AsKdlNode, FromKdlNode, NodeId};
+
+fn read<T: for<'doc> FromKdlNode<'doc, (), Error = miette::Report>>(src: &str) -> miette::Result<Vec<T>> {
+	let doc = src.parse::<kdl::KdlDocument>().expect("valid kdl");
+	let nodes = doc.nodes().iter();
+	nodes.map(|node| Node::new(node, &()).to::<T>()).collect()
+}
+
+fn write_all<T: AsKdlNode>(values: &[T], name: impl Fn(&T) -> &str) -> String {
+	let mut doc = kdl::KdlDocument::new();
+	for value in values {
+		doc.nodes_mut().push(value.as_kdl().build(name(value)));
+	}
+	doc.to_string()
+}
+
+#[derive(FromKdlNode, AsKdlNode, NodeId, Debug, PartialEq)]
+#[kdl(rename_all = "kebab-case")]
+enum ByName {
+	Circle(f64),
+	Rect { width: u32, height: u32 },
+	Empty,
+}
+
+#[derive(FromKdlNode, AsKdlNode, Debug, PartialEq)]
+#[kdl(tag = "type", rename_all = "lowercase")]
+enum ByType {
+	Circle(f64),
+	Rect {
+		#[kdl(arg)]
+		width: u32,
+		#[kdl(arg)]
+		height: u32,
+	},
+	Labeled {
+		label: String,
+	},
+	Empty,
+}
+
+#[derive(FromKdlNode, AsKdlNode, Debug, PartialEq)]
+#[kdl(tag_prop = "kind", rename_all = "lowercase")]
+enum ByProp {
+	Circle(f64),
+	#[kdl(rename = "box")]
+	Rect {
+		#[kdl(child = "size")]
+		size: Size,
+	},
+}
+
+#[derive(FromKdlNode, AsKdlNode, Debug, PartialEq)]
+struct Size(u32, u32);
+
+#[test]
+fn node_name() -> miette::Result<()> {
+	let values = read::<ByName>("circle 2.5\nrect width=3 height=4\nempty")?;
+	assert_eq!(
+		values,
+		vec![ByName::Circle(2.5), ByName::Rect { width: 3, height: 4 }, ByName::Empty]
+	);
+	assert_eq!(ByName::id(), "by-name");
+	let written = write_all(&values, |value| value.variant_name());
+	assert_eq!(written, "circle 2.5\nrect width=3 height=4\nempty\n");
+	Ok(())
+}
+
+#[test]
+fn type_annotation() -> miette::Result<()> {
+	let src = "shape (circle)2.5\nshape (rect)3 4\nshape (labeled)#null label=hi\nshape (empty)#null";
+	let values = read::<ByType>(src)?;
+	assert_eq!(
+		values,
+		vec![
+			ByType::Circle(2.5),
+			ByType::Rect { width: 3, height: 4 },
+			ByType::Labeled { label: "hi".into() },
+			ByType::Empty
+		]
+	);
+	let written = write_all(&values, |_| "shape");
+	assert_eq!(written, format!("{src}\n"));
+	Ok(())
+}
+
+#[derive(FromKdlNode, AsKdlNode, Debug, PartialEq)]
+#[kdl(tag = "type", rename_all = "lowercase")]
+enum Endpoint {
+	Optional {
+		#[kdl(arg)]
+		port: Option<u16>,
+		name: String,
+	},
+}
+
+#[test]
+fn type_annotation_optional_arg() -> miette::Result<()> {
+	let values = vec![
+		Endpoint::Optional {
+			port: Some(80),
+			name: "web".into(),
+		},
+		Endpoint::Optional {
+			port: None,
+			name: "local".into(),
+		},
+	];
+	let written = write_all(&values, |_| "endpoint");
+	assert_eq!(
+		written,
+		"endpoint (optional)80 name=web\nendpoint (optional)#null name=local\n"
+	);
+	assert_eq!(read::<Endpoint>(&written)?, values);
+	Ok(())
+}
+
+#[test]
+fn property() -> miette::Result<()> {
+	let src = "shape kind=circle 2.5\nshape kind=box {\n    size 1 2\n}";
+	let values = read::<ByProp>(src)?;
+	assert_eq!(values, vec![ByProp::Circle(2.5), ByProp::Rect { size: Size(1, 2) }]);
+	let written = write_all(&values, |_| "shape");
+	assert_eq!(written, format!("{src}\n"));
+	Ok(())
+}
+
+#[test]
+fn unknown_variant() {
+	let err = read::<ByType>("shape (triangle)3").unwrap_err();
+	let help = err.help().map(|help| help.to_string());
+	assert_eq!(help.as_deref(), Some("expected one of: circle, rect, labeled, empty"));
+
+	let err = read::<ByType>("shape 3").unwrap_err();
+	assert!(err.to_string().contains("missing a type"), "{err}");
+}
+
+#[test]
+fn builder_arg_type() {
+	let node = builder::Node::default()
+		.with(builder::Property("key", builder::Value(1)))
+		.with(builder::Value(2))
+		.with_arg_type("ty");
+	assert_eq!(node.build("node").to_string(), "node key=1 (ty)2\n");
+}
@@ -0,0 +1,269 @@
+use kdlize::{reader::Node, FromKdlNode};
+
+fn parse(src: &str) -> kdl::KdlDocument {
+	src.parse::<kdl::KdlDocument>().expect("valid kdl")
+}
+
+fn read<'doc, T: FromKdlNode<'doc, ()>>(doc: &'doc kdl::KdlDocument) -> Result<T, T::Error> {
+	Node::new(&doc.nodes()[0], &()).to::<T>()
+}
+
+#[derive(FromKdlNode, Debug, PartialEq)]
+struct Tls {
+	#[kdl(arg)]
+	cert: String,
+	key: Option<String>,
+}
+
+#[derive(FromKdlNode, Debug, PartialEq)]
+struct Route(String, #[kdl(prop = "to")] String);
+
+#[derive(FromKdlNode, Debug, PartialEq)]
+struct Server {
+	#[kdl(arg)]
+	name: String,
+	#[kdl(arg)]
+	alias: Option<String>,
+	port: u16,
+	#[kdl(prop = "max-connections", default)]
+	max_connections: u32,
+	#[kdl(prop, default = "default_timeout")]
+	timeout: u32,
+	#[kdl(child)]
+	tls: Option<Tls>,
+	#[kdl(children = "route")]
+	routes: Vec<Route>,
+	#[kdl(child = "owner", value)]
+	owner: String,
+	#[kdl(children = "tag", value)]
+	tags: Vec<String>,
+}
+
+fn default_timeout() -> u32 {
+	30
+}
+
+#[test]
+fn struct_all_kinds() -> miette::Result<()> {
+	let doc = parse(
+		r#"
+		server "main" port=8080 max-connections=10 {
+			tls "cert.pem"
+			route "/" to="index"
+			route "/api" to="api"
+			owner "ops"
+			tag "a"
+			tag "b"
+		}
+		"#,
+	);
+	let server = read::<Server>(&doc)?;
+	assert_eq!(
+		server,
+		Server {
+			name: "main".into(),
+			alias: None,
+			port: 8080,
+			max_connections: 10,
+			timeout: 30,
+			tls: Some(Tls {
+				cert: "cert.pem".into(),
+				key: None,
+			}),
+			routes: vec![Route("/".into(), "index".into()), Route("/api".into(), "api".into())],
+			owner: "ops".into(),
+			tags: vec!["a".into(), "b".into()],
+		}
+	);
+	Ok(())
+}
+
+#[test]
+fn missing_required() {
+	let doc = parse(r#"server "main" { owner "ops"; }"#);
+	let err = read::<Server>(&doc).unwrap_err();
+	assert!(err.to_string().contains("port"), "{err}");
+
+	let doc = parse(r#"server "main" port=80"#);
+	let err = read::<Server>(&doc).unwrap_err();
+	assert!(err.to_string().contains("owner"), "{err}");
+}
+
+#[test]
+fn wrong_value_type() {
+	let doc = parse(r#"server "main" port="eighty" { owner "ops"; }"#);
+	assert!(read::<Server>(&doc).is_err());
+}
+
+#[test]
+fn borrowed_fields() -> miette::Result<()> {
+	#[derive(FromKdlNode)]
+	struct Borrowed<'a> {
+		#[kdl(arg)]
+		name: &'a str,
+	}
+	let doc = parse(r#"node "value""#);
+	assert_eq!(read::<Borrowed>(&doc)?.name, "value");
+	Ok(())
+}
+
+#[test]
+fn specific_context() -> miette::Result<()> {
+	struct Multiplier(u32);
+
+	struct Scaled(u32);
+	impl<'doc> FromKdlNode<'doc, Multiplier> for Scaled {
+		type Error = miette::Report;
+		fn from_kdl(node: &mut Node<'doc, Multiplier>) -> Result<Self, Self::Error> {
+			let value = kdlize::reader::EntryExt::to::<u32>(node.next()?)?;
+			Ok(Self(value * node.context().0))
+		}
+	}
+
+	#[derive(FromKdlNode)]
+	#[kdl(context = Multiplier)]
+	struct Wrapper {
+		#[kdl(child)]
+		scaled: Scaled,
+	}
+
+	let doc = parse("node { scaled 4; }");
+	let ctx = Multiplier(3);
+	let wrapper = Node::new(&doc.nodes()[0], &ctx).to::<Wrapper>()?;
+	assert_eq!(wrapper.scaled.0, 12);
+	Ok(())
+}
+
+#[test]
+fn rename_all_fields() -> miette::Result<()> {
+	#[derive(FromKdlNode)]
+	#[kdl(rename_all = "kebab-case")]
+	struct Limits {
+		max_connections: u32,
+		#[kdl(child)]
+		idle_timeout: Option<Timeout>,
+	}
+	#[derive(FromKdlNode)]
+	struct Timeout(u32);
+
+	let doc = parse("limits max-connections=4 { idle-timeout 5; }");
+	let limits = read::<Limits>(&doc)?;
+	assert_eq!(limits.max_connections, 4);
+	assert_eq!(limits.idle_timeout.map(|timeout| timeout.0), Some(5));
+	Ok(())
+}
+
+#[test]
+fn deny_unknown_fields() -> miette::Result<()> {
+	#[derive(FromKdlNode, Debug)]
+	#[kdl(deny_unknown_fields)]
+	struct Style {
+		color: String,
+		#[kdl(child)]
+		border: Option<Tls>,
+	}
+
+	let doc = parse("style color=red");
+	let style = read::<Style>(&doc)?;
+	assert_eq!((style.color.as_str(), style.border), ("red", None));
+
+	let doc = parse("style color=red colr=blue");
+	let err = read::<Style>(&doc).expect_err("colr is not a field");
+	let unconsumed = err.downcast_ref::<kdlize::error::Unconsumed>().expect("is unconsumed");
+	assert_eq!(unconsumed.unused.len(), 1);
+	assert_eq!(
+		unconsumed.unused[0].suggestion.as_deref(),
+		Some("did you mean \"color\"?")
+	);
+	Ok(())
+}
+
+#[test]
+#[allow(dead_code)]
+fn error_path() {
+	#[derive(FromKdlNode)]
+	struct Cert {
+		path: u32,
+	}
+	#[derive(FromKdlNode)]
+	struct Tls {
+		#[kdl(child)]
+		cert: Cert,
+	}
+	#[derive(FromKdlNode)]
+	struct Server {
+		#[kdl(child)]
+		tls: Tls,
+	}
+	#[derive(FromKdlNode)]
+	struct Config {
+		#[kdl(children = "server")]
+		servers: Vec<Server>,
+	}
+
+	let doc = parse("config { server { tls { cert path=1; }; }; server { tls { cert path=x; }; }; }");
+	let Err(err) = read::<Config>(&doc) else {
+		panic!("second cert path is not an integer");
+	};
+	let message = err.to_string();
+	assert!(
+		message.contains("document > config > server[1] > tls > cert@path"),
+		"{message}"
+	);
+}
+
+#[test]
+#[allow(dead_code)]
+fn accumulate_errors() {
+	#[derive(FromKdlNode, Debug)]
+	struct Limits {
+		max: u32,
+		min: u32,
+		#[kdl(children = "server")]
+		servers: Vec<Tls>,
+	}
+
+	let doc = parse("limits max=x min=y { server; server a; }");
+	let diagnostics = kdlize::reader::Diagnostics::default();
+	let reader = Node::new(&doc.nodes()[0], &()).with_diagnostics(diagnostics.clone());
+	let result = reader.clone().to::<Limits>();
+	let err = diagnostics.finish(result).expect_err("has invalid fields");
+	let errors = err
+		.downcast_ref::<kdlize::error::MultipleErrors>()
+		.expect("has multiple errors");
+	let messages = errors.errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+	assert_eq!(messages.len(), 3, "{messages:?}");
+	assert!(messages[2].contains("server[0]"), "{messages:?}");
+
+	// Without a sink, reading stops at the first error
+	let err = read::<Limits>(&doc).expect_err("has invalid fields");
+	assert!(err.to_string().contains("limits@max"), "{err}");
+}
+
+#[test]
+fn document_root() -> miette::Result<()> {
+	#[derive(FromKdlNode, Debug, PartialEq)]
+	#[kdl(deny_unknown_fields)]
+	struct Config {
+		#[kdl(child = "name", value)]
+		name: String,
+		#[kdl(children = "tls")]
+		tls: Vec<Tls>,
+	}
+
+	let doc = parse("name example\ntls cert.pem key=key.pem\ntls other.pem");
+	let config = kdlize::reader::Document::new(&doc, &()).to::<Config>()?;
+	assert_eq!(config.name, "example");
+	assert_eq!(config.tls.len(), 2);
+
+	let doc = parse("name example\ntsl cert.pem");
+	let err = kdlize::reader::Document::new(&doc, &())
+		.to::<Config>()
+		.expect_err("tsl is unknown");
+	let unconsumed = err.downcast_ref::<kdlize::error::Unconsumed>().expect("is unconsumed");
+	assert_eq!(
+		unconsumed.unused[0].suggestion.as_deref(),
+		Some("did you mean \"tls\"?")
+	);
+	Ok(())
+}
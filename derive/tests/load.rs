@@ -0,0 +1,88 @@
+use kdlize::{error::LoadErrorKind, AsKdlNode, FromKdlNode};
+use miette::Diagnostic;
+
+#[derive(FromKdlNode, AsKdlNode, Debug, PartialEq)]
+struct Server {
+	#[kdl(arg)]
+	name: String,
+	port: u16,
+}
+
+#[derive(FromKdlNode, AsKdlNode, Debug, PartialEq)]
+struct Config {
+	#[kdl(child = "title", value)]
+	title: String,
+	#[kdl(children = "server")]
+	servers: Vec<Server>,
+}
+
+fn config() -> Config {
+	Config {
+		title: "example".into(),
+		servers: vec![
+			Server {
+				name: "one".into(),
+				port: 80,
+			},
+			Server {
+				name: "two".into(),
+				port: 8080,
+			},
+		],
+	}
+}
+
+#[test]
+fn round_trip() -> Result<(), kdlize::error::LoadError> {
+	let src = kdlize::to_string(&config());
+	assert_eq!(src, "title example\nserver one port=80\nserver two port=8080\n");
+	assert_eq!(kdlize::from_str::<Config, _>(&src, &())?, config());
+	Ok(())
+}
+
+#[test]
+fn parse_error_has_source() {
+	let err = kdlize::from_str::<Config, _>("title \"unterminated", &()).expect_err("invalid kdl");
+	assert!(matches!(err.kind(), LoadErrorKind::Parse(_)));
+	assert!(err.source_code().is_some());
+}
+
+#[test]
+fn read_errors_are_collected() {
+	let src = "title example\nserver one port=x\nserver port=80";
+	let err = kdlize::from_str::<Config, _>(src, &()).expect_err("invalid servers");
+	let LoadErrorKind::Read(report) = err.kind() else {
+		panic!("expected a read error, found {err}");
+	};
+	let errors = report
+		.downcast_ref::<kdlize::error::MultipleErrors>()
+		.expect("has multiple errors");
+	assert_eq!(errors.errors.len(), 2);
+	assert!(err.source_code().is_some());
+	assert_eq!(err.related().map(Iterator::count), Some(2));
+}
+
+#[test]
+fn file() -> Result<(), kdlize::error::LoadError> {
+	let path = std::env::temp_dir().join(format!("kdlize-load-{}.kdl", std::process::id()));
+	kdlize::to_file(&path, &config()).expect("writes file");
+	let loaded = kdlize::from_file::<Config, _>(&path, &());
+	std::fs::remove_file(&path).expect("removes file");
+	assert_eq!(loaded?, config());
+
+	let err = kdlize::from_file::<Config, _>(&path, &()).expect_err("file was removed");
+	assert!(matches!(err.kind(), LoadErrorKind::Io { .. }));
+	Ok(())
+}
+
+#[test]
+fn patch_keeps_comments() {
+	let src = "// the name of the app\ntitle example\n\n// servers\nserver one port=80 // http\nserver two port=8080\n";
+	let mut doc = src.parse::<kdlize::kdl::KdlDocument>().expect("valid kdl");
+	let mut config = config();
+	config.servers[0].port = 443;
+	config.servers.pop();
+	kdlize::patch_document(&mut doc, &config);
+	let expected = "// the name of the app\ntitle example\n\n// servers\nserver one port=443 // http\n";
+	assert_eq!(doc.to_string(), expected);
+}
@@ -0,0 +1,60 @@
+// The types only exist to derive ids from.
+#![allow(dead_code)]
+
+use kdlize::NodeId;
+
+#[derive(NodeId)]
+struct ServerConfig;
+
+#[derive(NodeId)]
+#[kdl(id = "srv")]
+struct Renamed;
+
+#[derive(NodeId)]
+struct Generic<T: Clone, const N: usize>([T; N]);
+
+#[derive(NodeId)]
+struct Borrowed<'a>(&'a str);
+
+#[derive(NodeId)]
+#[kdl(tag = "type")]
+enum Typed {
+	First(u32),
+}
+
+#[derive(NodeId)]
+#[kdl(rename_all = "lowercase")]
+enum Named {
+	First,
+	#[kdl(rename = "2nd")]
+	Second(u32),
+}
+
+#[test]
+fn default_id() {
+	assert_eq!(ServerConfig::id(), "server-config");
+	assert_eq!(ServerConfig.get_id(), "server-config");
+}
+
+#[test]
+fn explicit_id() {
+	assert_eq!(Renamed::id(), "srv");
+	assert_eq!(Renamed.get_id(), "srv");
+}
+
+#[test]
+fn generics() {
+	assert_eq!(Generic::<u8, 2>::id(), "generic");
+	assert_eq!(Generic([1u8, 2]).get_id(), "generic");
+	assert_eq!(Borrowed("value").get_id(), "borrowed");
+}
+
+#[test]
+fn enums() {
+	assert_eq!(Typed::id(), "typed");
+	assert_eq!(Typed::First(0).get_id(), "typed");
+	assert_eq!(Named::id(), "named");
+	// The id of every variant is the id of the enum, as registries are keyed by `id`.
+	assert_eq!(Named::First.get_id(), "named");
+	assert_eq!(Named::Second(0).get_id(), "named");
+}
@@ -0,0 +1,115 @@
+use kdlize::{
+	builder::{self, IntoNodeBuilder},
+	error::UnknownNodeId,
+	reader::{Document, Node},
+	registry::{ChildById, HasRegistry, Registry, Tag, TypedById},
+	AsKdlNode, FromKdlNode, NodeId,
+};
+
+trait Shape: NodeId + AsKdlNode + std::fmt::Debug {
+	fn area(&self) -> f64;
+}
+
+#[derive(NodeId, FromKdlNode, AsKdlNode, Debug, PartialEq)]
+struct Circle {
+	#[kdl(arg)]
+	radius: f64,
+}
+impl Shape for Circle {
+	fn area(&self) -> f64 {
+		std::f64::consts::PI * self.radius * self.radius
+	}
+}
+
+#[derive(NodeId, FromKdlNode, AsKdlNode, Debug, PartialEq)]
+struct Rect {
+	width: f64,
+	height: f64,
+}
+impl Shape for Rect {
+	fn area(&self) -> f64 {
+		self.width * self.height
+	}
+}
+
+impl AsKdlNode for Box<dyn Shape> {
+	fn as_kdl(&self) -> builder::Node {
+		TypedById(self.as_ref()).into_node()
+	}
+}
+
+struct Context {
+	shapes: Registry<dyn Shape, Context>,
+}
+impl Context {
+	fn new(tag: Tag) -> Self {
+		let shapes = Registry::<dyn Shape, Self>::new(tag)
+			.with::<Circle>(|value| Box::new(value))
+			.with::<Rect>(|value| Box::new(value));
+		Self { shapes }
+	}
+}
+impl HasRegistry<dyn Shape> for Context {
+	fn registry(&self) -> &Registry<dyn Shape, Self> {
+		&self.shapes
+	}
+}
+
+#[derive(FromKdlNode, AsKdlNode, Debug)]
+#[kdl(context = Context)]
+struct Scene {
+	#[kdl(children = "shape")]
+	shapes: Vec<Box<dyn Shape>>,
+}
+
+#[test]
+fn by_type() -> miette::Result<()> {
+	let src = "shape (circle)1.0\nshape (rect)#null width=2.0 height=3.0\n";
+	let doc = src.parse::<kdl::KdlDocument>().expect("valid kdl");
+	let ctx = Context::new(Tag::Type);
+	let scene = Document::new(&doc, &ctx).to::<Scene>()?;
+	let areas = scene.shapes.iter().map(|shape| shape.area()).collect::<Vec<_>>();
+	assert_eq!(areas, [std::f64::consts::PI, 6.0]);
+
+	assert_eq!(kdlize::to_string(&scene), src);
+	Ok(())
+}
+
+#[test]
+fn by_name() -> miette::Result<()> {
+	let src = "circle 1.0\nrect width=2.0 height=3.0\n";
+	let doc = src.parse::<kdl::KdlDocument>().expect("valid kdl");
+	let ctx = Context::new(Tag::Name);
+	let shapes = Document::new(&doc, &ctx)
+		.iter_children()
+		.to::<Box<dyn Shape>>()
+		.collect::<Vec<_>>()?;
+	assert_eq!(shapes.len(), 2);
+	assert_eq!(shapes[1].get_id(), "rect");
+
+	let mut node = builder::Node::default();
+	for shape in &shapes {
+		node.push(ChildById(shape.as_ref()));
+	}
+	let mut written = node.into_document();
+	written.autoformat();
+	assert_eq!(written.to_string(), src);
+	Ok(())
+}
+
+#[test]
+fn unknown_id() {
+	let doc = "shape (cirle)1.0".parse::<kdl::KdlDocument>().expect("valid kdl");
+	let ctx = Context::new(Tag::Type);
+	let mut node = Node::new(&doc.nodes()[0], &ctx);
+	let err = node.to::<Box<dyn Shape>>().expect_err("unregistered id");
+	let err = err.downcast_ref::<UnknownNodeId>().expect("unknown id error");
+	assert_eq!(err.id, "cirle");
+	assert_eq!(err.expected, ["circle", "rect"]);
+	assert_eq!(err.to_string(), "Node `document > shape` has unknown id \"cirle\"");
+	let label = miette::Diagnostic::labels(err).and_then(|mut labels| labels.next());
+	assert_eq!(
+		label.and_then(|label| label.label().map(str::to_owned)).as_deref(),
+		Some("did you mean \"circle\"?")
+	);
+}
@@ -0,0 +1,57 @@
+use kdlize::{reader::EntryExt, AsKdlValue, FromKdlValue};
+
+#[derive(FromKdlValue, AsKdlValue, Debug, PartialEq, Clone, Copy)]
+#[kdl(rename_all = "kebab-case")]
+enum Mode {
+	Fast,
+	SafeChecks,
+	#[kdl(rename = "paranoid")]
+	Slow,
+}
+
+#[derive(FromKdlValue, AsKdlValue, Debug, PartialEq)]
+enum Level {
+	Info,
+	Warn,
+}
+
+#[test]
+fn names() {
+	assert_eq!(Mode::Fast.as_kdl(), kdl::KdlValue::String("fast".into()));
+	assert_eq!(Mode::SafeChecks.as_kdl(), kdl::KdlValue::String("safe-checks".into()));
+	assert_eq!(Mode::Slow.as_kdl(), kdl::KdlValue::String("paranoid".into()));
+	assert_eq!(Level::Warn.as_kdl(), kdl::KdlValue::String("Warn".into()));
+}
+
+#[test]
+fn round_trip() {
+	for mode in [Mode::Fast, Mode::SafeChecks, Mode::Slow] {
+		assert_eq!(Mode::from_kdl(&mode.as_kdl()), Ok(mode));
+	}
+}
+
+#[test]
+fn unknown_variant() {
+	let err = Mode::from_kdl(&kdl::KdlValue::String("slow".into())).unwrap_err();
+	let kdlize::error::InvalidVariant::Unknown(unknown) = &err else {
+		panic!("expected unknown variant, got {err:?}");
+	};
+	assert_eq!(unknown.expected, &["fast", "safe-checks", "paranoid"]);
+	let help = miette::Diagnostic::help(&err).map(|help| help.to_string());
+	assert_eq!(help.as_deref(), Some("expected one of: fast, safe-checks, paranoid"));
+}
+
+#[test]
+fn type_mismatch() {
+	let err = Level::from_kdl(&kdl::KdlValue::Integer(1)).unwrap_err();
+	assert!(matches!(err, kdlize::error::InvalidVariant::TypeMismatch(_)));
+}
+
+#[test]
+fn entry_diagnostic_has_help() {
+	let doc = "node mode=turbo".parse::<kdl::KdlDocument>().expect("valid kdl");
+	let entry = doc.nodes()[0].entry("mode").unwrap();
+	let err = entry.to::<Mode>().unwrap_err();
+	let help = miette::Diagnostic::help(&err).map(|help| help.to_string());
+	assert_eq!(help.as_deref(), Some("expected one of: fast, safe-checks, paranoid"));
+}
@@ -0,0 +1,161 @@
+//! Collections read from the remaining positional arguments of a node, see [`FromKdlArgs`].
+
+use crate::{error::InvalidArgs, reader::Node, FromKdlArgs, FromKdlValue};
+use std::collections::{BTreeSet, HashSet, VecDeque};
+
+macro_rules! impl_fromkdlargs_collection {
+	($target:ident < T $(: $bound:path)* >) => {
+		impl<'doc, T> FromKdlArgs<'doc> for $target<T>
+		where
+			T: FromKdlValue<'doc> $(+ $bound)*,
+			miette::Report: From<T::Error>,
+		{
+			fn from_args<Context>(node: &mut Node<'doc, Context>) -> Result<Self, InvalidArgs> {
+				Ok(node.rest::<T>()?.into_iter().collect())
+			}
+		}
+	};
+}
+
+impl_fromkdlargs_collection!(Vec<T>);
+impl_fromkdlargs_collection!(VecDeque<T>);
+impl_fromkdlargs_collection!(BTreeSet<T: Ord>);
+
+impl<'doc, T, S> FromKdlArgs<'doc> for HashSet<T, S>
+where
+	T: FromKdlValue<'doc> + Eq + std::hash::Hash,
+	S: std::hash::BuildHasher + Default,
+	miette::Report: From<T::Error>,
+{
+	fn from_args<Context>(node: &mut Node<'doc, Context>) -> Result<Self, InvalidArgs> {
+		Ok(node.rest::<T>()?.into_iter().collect())
+	}
+}
+
+impl<'doc, T, const N: usize> FromKdlArgs<'doc> for [T; N]
+where
+	T: FromKdlValue<'doc>,
+	miette::Report: From<T::Error>,
+{
+	fn from_args<Context>(node: &mut Node<'doc, Context>) -> Result<Self, InvalidArgs> {
+		node.expect_args(N)?;
+		let values = node.rest::<T>()?;
+		Ok(values
+			.try_into()
+			.unwrap_or_else(|_| unreachable!("the number of arguments was checked")))
+	}
+}
+
+macro_rules! impl_fromkdlargs_tuple {
+	($count:expr; $($item:ident),+) => {
+		impl<'doc, $($item),+> FromKdlArgs<'doc> for ($($item,)+)
+		where
+			$(
+				$item: FromKdlValue<'doc>,
+				miette::Report: From<$item::Error>,
+			)+
+		{
+			fn from_args<Context>(node: &mut Node<'doc, Context>) -> Result<Self, InvalidArgs> {
+				use crate::reader::EntryExt;
+				node.expect_args($count)?;
+				Ok(($({
+					let entry = node.next().expect("the number of arguments was checked");
+					entry.to::<$item>().map_err(|err| err.with_path(node.entry_path(entry)))?
+				},)+))
+			}
+		}
+	};
+}
+
+impl_fromkdlargs_tuple!(1; A);
+impl_fromkdlargs_tuple!(2; A, B);
+impl_fromkdlargs_tuple!(3; A, B, C);
+impl_fromkdlargs_tuple!(4; A, B, C, D);
+impl_fromkdlargs_tuple!(5; A, B, C, D, E);
+impl_fromkdlargs_tuple!(6; A, B, C, D, E, F);
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{
+		builder::{self, Values},
+		error::ArgsLength,
+	};
+
+	fn node(src: &str) -> kdl::KdlNode {
+		src.parse::<kdl::KdlNode>().expect("valid kdl")
+	}
+
+	#[test]
+	fn rest() {
+		let kdl = node("tags first \"second\" key=1 third");
+		let mut node = Node::new(&kdl, &());
+		assert_eq!(node.next().map(|entry| entry.value().clone()), Ok("first".into()));
+		assert_eq!(node.remaining_args(), 2);
+		assert_eq!(node.rest::<String>(), Ok(vec!["second".to_owned(), "third".to_owned()]));
+		assert_eq!(node.remaining_args(), 0);
+		assert_eq!(node.rest::<String>(), Ok(Vec::new()));
+	}
+
+	#[test]
+	fn collections() {
+		let kdl = node("ports 80 443 80");
+		let ports = Node::new(&kdl, &()).args::<Vec<u16>>();
+		assert_eq!(ports, Ok(vec![80, 443, 80]));
+		let ports = Node::new(&kdl, &()).args::<HashSet<u16>>();
+		assert_eq!(ports, Ok(HashSet::from([80, 443])));
+		let ports = Node::new(&kdl, &()).args::<BTreeSet<u16>>();
+		assert_eq!(ports, Ok(BTreeSet::from([80, 443])));
+
+		let err = Node::new(&node("ports 80 http"), &()).args::<Vec<u16>>();
+		let Err(InvalidArgs::Parse(err)) = err else {
+			panic!("expected a parse error, found {err:?}");
+		};
+		assert!(err
+			.to_string()
+			.starts_with("Failed to parse value at document > ports@1:"));
+	}
+
+	#[test]
+	fn fixed_length() {
+		let kdl = node("color 255 128 0");
+		assert_eq!(Node::new(&kdl, &()).args::<[u8; 3]>(), Ok([255, 128, 0]));
+		let mut reader = Node::new(&kdl, &());
+		assert_eq!(reader.args::<(u8, u8, u8)>(), Ok((255, 128, 0)));
+		assert_eq!(reader.finish(), Ok(()));
+		let mut reader = Node::new(&kdl, &());
+		reader.next().expect("first argument");
+		assert_eq!(reader.args::<(u8, u8)>(), Ok((128, 0)));
+
+		let err = Node::new(&kdl, &()).args::<[u8; 4]>().expect_err("too few arguments");
+		let InvalidArgs::Length(err) = err else {
+			panic!("expected a length error, found {err:?}");
+		};
+		assert_eq!((err.expected, err.found), (4, 3));
+		assert_eq!(
+			err.to_string(),
+			"Node `document > color` has 3 positional arguments left to read, but expected 4"
+		);
+		let err = Node::new(&kdl, &()).args::<(u8, u8)>();
+		assert!(matches!(
+			err,
+			Err(InvalidArgs::Length(ArgsLength {
+				expected: 2,
+				found: 3,
+				..
+			}))
+		));
+	}
+
+	#[test]
+	fn values() {
+		let ports = vec![80u16, 443];
+		let node = builder::Node::default()
+			.with(Values(&ports))
+			.with(Values(["a", "b"]))
+			.build("ports");
+		assert_eq!(node.to_string(), "ports 80 443 a b\n");
+		let mut reader = Node::new(&node, &());
+		assert_eq!(reader.args::<(u16, u16, String, String)>().map(|args| args.0), Ok(80));
+	}
+}
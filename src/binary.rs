@@ -0,0 +1,223 @@
+//! Values for binary data (`Vec<u8>`, and `bytes::Bytes` via the `bytes` feature), which are written as
+//! strings encoded as `(base64)`, the type annotation reserved by KDL, or `(hex)`.
+//! Other encodings are chosen when writing via the builder's `Typed`, e.g. `Typed("hex", Value(&bytes))`.
+
+use crate::{
+	error::{InvalidString, MalformedValue, TypeAnnotationMismatch, ValueTypeMismatch},
+	AsKdlValue, FromKdlValue,
+};
+use base64::Engine;
+
+const BASE64: &str = "base64";
+const HEX: &str = "hex";
+
+/// An encoding of binary data as text, selected by the type annotation of the value.
+#[derive(Clone, Copy)]
+enum Encoding {
+	Base64,
+	Hex,
+}
+
+impl Encoding {
+	/// The encoding for a type annotation, where unannotated values are base64.
+	fn from_annotation(ty: Option<&str>) -> Option<Self> {
+		match ty {
+			None | Some(BASE64) => Some(Self::Base64),
+			Some(HEX) => Some(Self::Hex),
+			Some(_) => None,
+		}
+	}
+
+	fn format(self) -> &'static str {
+		match self {
+			Self::Base64 => "base64 text, e.g. aGVsbG8=",
+			Self::Hex => "hex digits, e.g. 68656c6c6f",
+		}
+	}
+
+	fn encode(self, bytes: &[u8]) -> String {
+		match self {
+			Self::Base64 => base64::engine::general_purpose::STANDARD.encode(bytes),
+			Self::Hex => bytes.iter().map(|byte| format!("{byte:02x}")).collect(),
+		}
+	}
+
+	fn decode(self, text: &str) -> Result<Vec<u8>, String> {
+		// Whitespace is ignored, so that long values can be written as multi-line strings.
+		let text = text.chars().filter(|ch| !ch.is_whitespace()).collect::<String>();
+		match self {
+			Self::Base64 => base64::engine::general_purpose::STANDARD
+				.decode(text)
+				.map_err(|err| err.to_string()),
+			Self::Hex => {
+				if text.len() % 2 != 0 {
+					return Err("it has an odd number of digits".to_owned());
+				}
+				// Both characters are checked, as `from_str_radix` also accepts a sign, e.g. `+f`.
+				let bytes = text.as_bytes().chunks(2).map(|digits| {
+					let digits = std::str::from_utf8(digits).ok()?;
+					if !digits.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+						return None;
+					}
+					u8::from_str_radix(digits, 16).ok()
+				});
+				bytes
+					.collect::<Option<Vec<_>>>()
+					.ok_or_else(|| "it contains characters which are not hex digits".to_owned())
+			}
+		}
+	}
+}
+
+fn decode(value: &kdl::KdlValue, ty: Option<&str>) -> Result<Vec<u8>, InvalidString> {
+	let Some(encoding) = Encoding::from_annotation(ty) else {
+		let found = ty.unwrap_or_default().to_owned();
+		return Err(TypeAnnotationMismatch {
+			expected: BASE64,
+			found,
+		}
+		.into());
+	};
+	let kdl::KdlValue::String(text) = value else {
+		return Err(ValueTypeMismatch::new(value, "String").into());
+	};
+	encoding.decode(text).map_err(|reason| {
+		let malformed = MalformedValue {
+			type_name: "bytes",
+			value: text.clone(),
+			format: encoding.format(),
+			reason,
+		};
+		malformed.into()
+	})
+}
+
+/// Encodes bytes as the value of an entry annotated `ty`, where unknown annotations are encoded as base64.
+fn encode(bytes: &[u8], ty: &str) -> kdl::KdlValue {
+	let encoding = Encoding::from_annotation(Some(ty)).unwrap_or(Encoding::Base64);
+	kdl::KdlValue::String(encoding.encode(bytes))
+}
+
+impl<'doc> FromKdlValue<'doc> for Vec<u8> {
+	type Error = InvalidString;
+	fn from_kdl(value: &'doc kdl::KdlValue) -> Result<Self, Self::Error> {
+		decode(value, None)
+	}
+
+	fn from_kdl_typed(value: &'doc kdl::KdlValue, ty: Option<&'doc str>) -> Result<Self, Self::Error> {
+		decode(value, ty)
+	}
+}
+
+impl AsKdlValue for [u8] {
+	fn as_kdl(&self) -> kdl::KdlValue {
+		encode(self, BASE64)
+	}
+
+	fn type_annotation(&self) -> Option<&'static str> {
+		Some(BASE64)
+	}
+
+	fn as_kdl_typed(&self, ty: &str) -> kdl::KdlValue {
+		encode(self, ty)
+	}
+}
+impl AsKdlValue for Vec<u8> {
+	fn as_kdl(&self) -> kdl::KdlValue {
+		self.as_slice().as_kdl()
+	}
+
+	fn type_annotation(&self) -> Option<&'static str> {
+		Some(BASE64)
+	}
+
+	fn as_kdl_typed(&self, ty: &str) -> kdl::KdlValue {
+		self.as_slice().as_kdl_typed(ty)
+	}
+}
+
+#[cfg(feature = "bytes")]
+impl<'doc> FromKdlValue<'doc> for bytes::Bytes {
+	type Error = InvalidString;
+	fn from_kdl(value: &'doc kdl::KdlValue) -> Result<Self, Self::Error> {
+		Ok(decode(value, None)?.into())
+	}
+
+	fn from_kdl_typed(value: &'doc kdl::KdlValue, ty: Option<&'doc str>) -> Result<Self, Self::Error> {
+		Ok(decode(value, ty)?.into())
+	}
+}
+#[cfg(feature = "bytes")]
+impl AsKdlValue for bytes::Bytes {
+	fn as_kdl(&self) -> kdl::KdlValue {
+		self.as_ref().as_kdl()
+	}
+
+	fn type_annotation(&self) -> Option<&'static str> {
+		Some(BASE64)
+	}
+
+	fn as_kdl_typed(&self, ty: &str) -> kdl::KdlValue {
+		self.as_ref().as_kdl_typed(ty)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{
+		builder::{Node, Typed, Value},
+		reader::EntryExt,
+	};
+
+	fn read(src: &str) -> Result<Vec<u8>, InvalidString> {
+		let doc = src.parse::<kdl::KdlDocument>().expect("valid kdl");
+		let entry = &doc.nodes()[0].entries()[0];
+		Vec::<u8>::from_kdl_typed(entry.value(), entry.ty().map(kdl::KdlIdentifier::value))
+	}
+
+	#[test]
+	fn decode() {
+		assert_eq!(read("key (base64)\"aGVsbG8=\""), Ok(b"hello".to_vec()));
+		assert_eq!(read("key \"aGVsbG8=\""), Ok(b"hello".to_vec()));
+		assert_eq!(read("key (hex)\"68656C6c6f\""), Ok(b"hello".to_vec()));
+		assert_eq!(
+			read("key (hex)\"\"\"\n    6865\n    6c6c6f\n    \"\"\""),
+			Ok(b"hello".to_vec())
+		);
+		assert_eq!(
+			read("key (sha256)\"aGVsbG8=\""),
+			Err(InvalidString::TypeAnnotation(TypeAnnotationMismatch {
+				expected: "base64",
+				found: "sha256".to_owned(),
+			}))
+		);
+		assert!(matches!(read("key (hex)\"abc\""), Err(InvalidString::Malformed(_))));
+		assert!(matches!(read("key (hex)\"zz\""), Err(InvalidString::Malformed(_))));
+		assert!(matches!(read("key (hex)\"+f+f\""), Err(InvalidString::Malformed(_))));
+		assert!(matches!(read("key \"not base64!\""), Err(InvalidString::Malformed(_))));
+		assert!(matches!(read("key 5"), Err(InvalidString::TypeMismatch(_))));
+	}
+
+	#[test]
+	fn encode() {
+		let bytes = b"hello".to_vec();
+		let node = Node::default()
+			.with(Value(&bytes))
+			.with(Typed("hex", Value(&bytes)))
+			.build("key");
+		assert_eq!(node.to_string(), "key (base64)\"aGVsbG8=\" (hex)\"68656c6c6f\"\n");
+		for entry in node.entries() {
+			assert_eq!(entry.to::<Vec<u8>>(), Ok(bytes.clone()));
+		}
+	}
+
+	#[cfg(feature = "bytes")]
+	#[test]
+	fn bytes() {
+		let bytes = bytes::Bytes::from_static(b"\x00\xff");
+		let node = Node::default().with(Typed("hex", Value(&bytes))).build("key");
+		assert_eq!(node.to_string(), "key (hex)\"00ff\"\n");
+		assert_eq!(node.entries()[0].to::<bytes::Bytes>(), Ok(bytes));
+	}
+}
@@ -4,6 +4,11 @@ pub use node::*;
 mod entry;
 pub use entry::*;
 
+mod patch;
+
+mod comment;
+pub use comment::*;
+
 pub struct OmitIfEmpty<V>(pub V);
 
 pub struct OmitIfEqual<V, T>(pub V, pub T);
@@ -71,6 +76,16 @@ mod test {
 		assert_eq!(value.to_string(), expected.to_string());
 	}
 
+	#[test]
+	fn node_with_multilinevalue_escaped() {
+		let content = "a \"\"\" quote\nand C:\\path\\";
+		let value = Node::default().with(MultiLine(Value(content))).build("node");
+		let expected = "node \"\"\"\na \"\"\\\" quote\nand C:\\\\path\\\\\n\"\"\"\n";
+		assert_eq!(value.to_string(), expected);
+		let doc = value.to_string().parse::<kdl::KdlDocument>().expect("valid kdl");
+		assert_eq!(doc.nodes()[0].entries()[0].value(), &kdl::KdlValue::from(content));
+	}
+
 	#[test]
 	fn child_untyped_value() {
 		let value = {
@@ -319,4 +334,56 @@ mod test {
 		};
 		assert_eq!(value.to_string(), expected.to_string());
 	}
+
+	#[test]
+	fn comments() {
+		let value = {
+			let mut node = Node::default();
+			node.push(Comment("the node"));
+			node.push(Commented(BlockComment("the host"), Value("localhost")));
+			node.push(Property("port", Value(80)));
+			node.push(Child(
+				"tls",
+				Commented(Comment("secure the connection\nif possible"), Value(true)),
+			));
+			node.push(Commented(Comment("all users"), Children("user", Value(["a", "b"]))));
+			node.build("node")
+		};
+		let expected = "// the node
+node /* the host */ localhost port=80 {
+    // secure the connection
+    // if possible
+    tls #true
+    // all users
+    user a
+    user b
+}
+";
+		assert_eq!(value.to_string(), expected);
+	}
+
+	#[test]
+	fn block_comment_delimiters() {
+		let value = Node::default()
+			.with(Commented(BlockComment("see /* above */ and */*/"), Value("localhost")))
+			.with(Property("port", Value(80)))
+			.build("node");
+		let expected = "node /* see / * above * / and * / * / */ localhost port=80\n";
+		assert_eq!(value.to_string(), expected);
+		let doc = value.to_string().parse::<kdl::KdlDocument>().expect("valid kdl");
+		let node = &doc.nodes()[0];
+		assert_eq!(node.entries().len(), 2);
+		assert_eq!(node.entries()[0].value(), &kdl::KdlValue::from("localhost"));
+		assert_eq!(node.get("port"), Some(&kdl::KdlValue::from(80)));
+	}
+
+	#[test]
+	fn document_comment() {
+		let value = Node::default()
+			.with(BlockComment("generated config"))
+			.with(Child("name", Value("app")));
+		let mut doc = value.into_document();
+		doc.autoformat();
+		assert_eq!(doc.to_string(), "/* generated config */\nname app\n");
+	}
 }
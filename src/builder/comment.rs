@@ -0,0 +1,109 @@
+use super::{Children, Entry, InnerValue, IntoNodeBuilder, Node, NodeComponent, OmitIfEmpty};
+
+/// Text which can be written as a KDL comment.
+pub trait AsComment {
+	fn as_comment(&self) -> String;
+}
+
+/// A `// line` comment, written on the line(s) before a node. Each line of the text is its own comment.
+pub struct Comment<S: AsRef<str>>(pub S);
+impl<S: AsRef<str>> AsComment for Comment<S> {
+	fn as_comment(&self) -> String {
+		let lines = self.0.as_ref().lines().map(|line| match line.trim_end() {
+			"" => "//".to_owned(),
+			line => format!("// {line}"),
+		});
+		lines.collect::<Vec<_>>().join("\n")
+	}
+}
+
+/// A `/* block */` comment, written before a node or inline before an entry.
+pub struct BlockComment<S: AsRef<str>>(pub S);
+impl<S: AsRef<str>> AsComment for BlockComment<S> {
+	fn as_comment(&self) -> String {
+		// Block comments nest, so the text can neither end the comment early nor open a nested one.
+		let text = self.0.as_ref().trim().replace("/*", "/ *").replace("*/", "* /");
+		format!("/* {text} */")
+	}
+}
+
+impl<S: AsRef<str>> NodeComponent for Comment<S> {
+	fn apply_to(self, builder: &mut Node) {
+		builder.comments.push(self.as_comment());
+	}
+}
+impl<S: AsRef<str>> NodeComponent for BlockComment<S> {
+	fn apply_to(self, builder: &mut Node) {
+		builder.comments.push(self.as_comment());
+	}
+}
+
+/// Attaches a comment to the child node(s) or the entry built from the inner value.
+pub struct Commented<C, V>(pub C, pub V);
+
+impl<C: AsComment, V: IntoNodeBuilder> IntoNodeBuilder for Commented<C, V> {
+	fn into_node(self) -> Node {
+		let mut node = self.1.into_node();
+		node.comments.push(self.0.as_comment());
+		node
+	}
+}
+
+impl<S: AsRef<str>, V: Into<Entry>> From<Commented<BlockComment<S>, V>> for Entry {
+	fn from(value: Commented<BlockComment<S>, V>) -> Self {
+		let entry: Entry = value.1.into();
+		entry.comment(value.0 .0)
+	}
+}
+impl<S: AsRef<str>, V: Into<Entry>> NodeComponent for Commented<BlockComment<S>, V> {
+	fn apply_to(self, builder: &mut Node) {
+		let entry: Entry = self.into();
+		entry.apply_to(builder);
+	}
+}
+impl<C, I, V> InnerValue for Commented<C, I>
+where
+	I: InnerValue<Inner = V>,
+{
+	type Inner = V;
+	fn inner(&self) -> &Self::Inner {
+		self.1.inner()
+	}
+}
+
+// A comment on a list of children is written once, before the first child.
+impl<C: AsComment, K: Into<kdl::KdlIdentifier>, V> NodeComponent for Commented<C, Children<K, V>>
+where
+	Children<K, V>: NodeComponent,
+{
+	fn apply_to(self, builder: &mut Node) {
+		comment_first_child(builder, self.0, self.1);
+	}
+}
+impl<C: AsComment, K: Into<kdl::KdlIdentifier>, V> NodeComponent for Commented<C, OmitIfEmpty<Children<K, V>>>
+where
+	OmitIfEmpty<Children<K, V>>: NodeComponent,
+{
+	fn apply_to(self, builder: &mut Node) {
+		comment_first_child(builder, self.0, self.1);
+	}
+}
+
+fn comment_first_child(builder: &mut Node, comment: impl AsComment, children: impl NodeComponent) {
+	let mut node = Node::default();
+	children.apply_to(&mut node);
+	if let Some(first) = node.children.first_mut() {
+		prepend_comment(first, &comment.as_comment());
+	}
+	builder.children.append(&mut node.children);
+}
+
+/// Writes a comment on the line(s) before a node, before any comments it already has.
+pub(super) fn prepend_comment(node: &mut kdl::KdlNode, comment: &str) {
+	if node.format().is_none() {
+		node.set_format(kdl::KdlNodeFormat::default());
+	}
+	if let Some(format) = node.format_mut() {
+		format.leading.insert_str(0, &format!("{comment}\n"));
+	}
+}
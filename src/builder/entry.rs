@@ -26,7 +26,27 @@ impl Entry {
 	}
 
 	pub fn value<V: AsKdlValue>(mut self, value: V) -> Self {
-		self.entry.set_value(value.as_kdl());
+		let value = value.as_kdl();
+		if let Some(format) = self.entry.format_mut() {
+			format.value_repr = value.to_string();
+		}
+		self.entry.set_value(value);
+		self
+	}
+
+	/// Writes a `/* block */` comment inline before the entry.
+	pub fn comment(mut self, text: impl AsRef<str>) -> Self {
+		let comment = super::AsComment::as_comment(&super::BlockComment(text));
+		match self.entry.format_mut() {
+			Some(format) => format.leading = format!(" {comment} {}", format.leading.trim_start()),
+			None => {
+				let mut format = kdl::KdlEntryFormat::default();
+				format.autoformat_keep = true;
+				format.leading = format!(" {comment} ");
+				format.value_repr = self.entry.value().to_string();
+				self.entry.set_format(format);
+			}
+		}
 		self
 	}
 }
@@ -43,14 +63,20 @@ impl<V> InnerValue for Value<&V> {
 		self.0
 	}
 }
-impl<V: AsKdlValue> Into<Entry> for Value<V> {
-	fn into(self) -> Entry {
+impl<V: AsKdlValue> From<Value<V>> for Entry {
+	fn from(value: Value<V>) -> Self {
 		let mut builder = Entry::default();
-		builder.entry.set_value(Some(self.0.as_kdl()));
+		builder.entry.set_value(Some(value.0.as_kdl()));
+		if let Some(ty) = value.0.type_annotation() {
+			builder.entry.set_ty(ty);
+		}
 		builder
 	}
 }
 
+/// Many positional arguments, one for each value of an iterator, e.g. `Values(&self.tags)`.
+pub struct Values<I>(pub I);
+
 pub struct MultiLine<V>(pub Value<V>);
 impl<V> InnerValue for MultiLine<&V> {
 	type Inner = V;
@@ -59,15 +85,16 @@ impl<V> InnerValue for MultiLine<&V> {
 	}
 }
 // TODO: Add associated-type to AsKdlValue to identify that the output is a string and limit MultiLine to string values
-impl<V: AsKdlValue> Into<Entry> for MultiLine<V> {
-	fn into(self) -> Entry {
-		let mut builder: Entry = self.0.into();
+impl<V: AsKdlValue> From<MultiLine<V>> for Entry {
+	fn from(value: MultiLine<V>) -> Self {
+		let mut builder: Entry = value.0.into();
 		if let Some(value_str) = builder.entry.value().as_string() {
+			let value_str = crate::format::escape_multi_line(value_str);
 			builder.entry.set_format({
 				let mut format = kdl::KdlEntryFormat::default();
 				format.autoformat_keep = true;
 				format.leading = " ".to_owned();
-				format.value_repr = format!("\"{value_str}\"");
+				format.value_repr = format!("\"\"\"\n{value_str}\n\"\"\"");
 				format
 			});
 		}
@@ -84,10 +111,12 @@ where
 		self.1.inner()
 	}
 }
-impl<Ty: Into<kdl::KdlIdentifier>, V: AsKdlValue> Into<Entry> for Typed<Ty, Value<V>> {
-	fn into(self) -> Entry {
-		let mut builder: Entry = self.1.into();
-		builder.entry.set_ty(self.0);
+impl<Ty: Into<kdl::KdlIdentifier>, V: AsKdlValue> From<Typed<Ty, Value<V>>> for Entry {
+	fn from(value: Typed<Ty, Value<V>>) -> Self {
+		let ty: kdl::KdlIdentifier = value.0.into();
+		let mut builder = Entry::default();
+		builder.entry.set_value(Some(value.1 .0.as_kdl_typed(ty.value())));
+		builder.entry.set_ty(ty);
 		builder
 	}
 }
@@ -102,10 +131,10 @@ where
 		self.1.inner()
 	}
 }
-impl<K: Into<kdl::KdlIdentifier>, V: Into<Entry>> Into<Entry> for Property<K, V> {
-	fn into(self) -> Entry {
-		let mut builder: Entry = self.1.into();
-		builder.entry.set_name(Some(self.0));
+impl<K: Into<kdl::KdlIdentifier>, V: Into<Entry>> From<Property<K, V>> for Entry {
+	fn from(value: Property<K, V>) -> Self {
+		let mut builder: Entry = value.1.into();
+		builder.entry.set_name(Some(value.0));
 		builder
 	}
 }
@@ -1,10 +1,12 @@
-use super::{Entry, OmitIfEmpty, OmitIfEqual, Property, Typed, Value};
+use super::{Entry, OmitIfEmpty, OmitIfEqual, Property, Typed, Value, Values};
 use crate::{AsKdlNode, AsKdlValue};
 
 #[derive(Default, Debug, Clone)]
 pub struct Node {
 	pub(super) entries: Vec<kdl::KdlEntry>,
 	pub(super) children: Vec<kdl::KdlNode>,
+	/// Comments written on the line(s) before the built node.
+	pub(super) comments: Vec<String>,
 }
 
 impl Node {
@@ -12,7 +14,14 @@ impl Node {
 		self.entries.is_empty() && self.children.is_empty()
 	}
 
-	pub fn into_document(self) -> kdl::KdlDocument {
+	/// Converts the children of this node into a document.
+	/// Comments on this node are written before the first node of the document, and its entries are dropped.
+	pub fn into_document(mut self) -> kdl::KdlDocument {
+		if let Some(first) = self.children.first_mut() {
+			for comment in self.comments.iter().rev() {
+				super::comment::prepend_comment(first, comment);
+			}
+		}
 		let mut doc = kdl::KdlDocument::new();
 		*doc.nodes_mut() = self.children;
 		doc
@@ -22,8 +31,12 @@ impl Node {
 		let Self {
 			mut entries,
 			mut children,
+			comments,
 		} = self;
 		let mut node = kdl::KdlNode::new(name);
+		for comment in comments.iter().rev() {
+			super::comment::prepend_comment(&mut node, comment);
+		}
 
 		node.entries_mut().append(&mut entries);
 
@@ -37,9 +50,9 @@ impl Node {
 	}
 }
 
-impl Into<kdl::KdlDocument> for Node {
-	fn into(self) -> kdl::KdlDocument {
-		self.into_document()
+impl From<Node> for kdl::KdlDocument {
+	fn from(value: Node) -> Self {
+		value.into_document()
 	}
 }
 
@@ -59,6 +72,20 @@ impl Node {
 		}
 		self
 	}
+
+	/// Sets the type annotation of the first positional argument,
+	/// inserting a null argument to hold the annotation if the node has no positional arguments.
+	pub fn with_arg_type(mut self, ty: impl Into<kdl::KdlIdentifier>) -> Self {
+		match self.entries.iter_mut().find(|entry| entry.name().is_none()) {
+			Some(entry) => entry.set_ty(ty),
+			None => {
+				let mut entry = kdl::KdlEntry::new(kdl::KdlValue::Null);
+				entry.set_ty(ty);
+				self.entries.insert(0, entry);
+			}
+		}
+		self
+	}
 }
 
 impl<T: NodeComponent> std::ops::Add<T> for Node {
@@ -81,10 +108,17 @@ impl NodeComponent for kdl::KdlNode {
 		builder.children.push(self);
 	}
 }
+/// Pushes the entry as-is, including `#null` values which other components omit.
+impl NodeComponent for kdl::KdlEntry {
+	fn apply_to(self, builder: &mut Node) {
+		builder.entries.push(self);
+	}
+}
 impl NodeComponent for Node {
 	fn apply_to(mut self, builder: &mut Node) {
 		builder.entries.append(&mut self.entries);
 		builder.children.append(&mut self.children);
+		builder.comments.append(&mut self.comments);
 	}
 }
 impl<V: AsKdlNode> NodeComponent for &V {
@@ -200,6 +234,28 @@ impl<V: AsKdlValue> NodeComponent for Value<V> {
 		entry.apply_to(builder);
 	}
 }
+impl<I> IntoNodeBuilder for Values<I>
+where
+	I: IntoIterator,
+	I::Item: AsKdlValue,
+{
+	fn into_node(self) -> Node {
+		let mut node = Node::default();
+		self.apply_to(&mut node);
+		node
+	}
+}
+impl<I> NodeComponent for Values<I>
+where
+	I: IntoIterator,
+	I::Item: AsKdlValue,
+{
+	fn apply_to(self, builder: &mut Node) {
+		for value in self.0 {
+			Value(value).apply_to(builder);
+		}
+	}
+}
 impl<V: AsKdlValue> NodeComponent for super::MultiLine<V> {
 	fn apply_to(self, builder: &mut Node) {
 		let entry: Entry = self.into();
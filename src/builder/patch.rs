@@ -0,0 +1,269 @@
+use super::Node;
+
+impl Node {
+	/// Updates an existing node (e.g. parsed from a user's file) to match this one.
+	/// Only the entries and children which differ are changed, so the comments and formatting
+	/// of everything else are kept. Children are matched by name and the order they appear in.
+	pub fn patch(self, existing: &mut kdl::KdlNode) {
+		let indent = leading_indent(existing.format().map(|format| format.leading.as_str()));
+		patch_entries(existing, self.entries);
+		match (self.children.is_empty(), existing.children_mut()) {
+			(true, None) => {}
+			(true, Some(_)) => {
+				existing.clear_children();
+				if let Some(format) = existing.format_mut() {
+					format.before_children.clear();
+				}
+			}
+			(false, Some(doc)) => patch_nodes(doc, self.children, Some(&indent)),
+			(false, None) => {
+				if let Some(format) = existing.format_mut() {
+					format.before_children = " ".into();
+				}
+				patch_nodes(existing.ensure_children(), self.children, Some(&indent));
+			}
+		}
+	}
+
+	/// Updates the nodes of an existing document to match the children of this node, see [`Node::patch`].
+	pub fn patch_document(self, existing: &mut kdl::KdlDocument) {
+		patch_nodes(existing, self.children, None);
+	}
+}
+
+fn same_entry(a: &kdl::KdlEntry, b: &kdl::KdlEntry) -> bool {
+	a.value() == b.value() && a.ty().map(kdl::KdlIdentifier::value) == b.ty().map(kdl::KdlIdentifier::value)
+}
+
+/// Replaces an entry with a new one, keeping the whitespace and comments around the old entry.
+fn replace_entry(existing: &mut kdl::KdlEntry, mut entry: kdl::KdlEntry) {
+	if let Some(format) = existing.format() {
+		entry.set_format(kdl::KdlEntryFormat {
+			value_repr: entry.value().to_string(),
+			leading: format.leading.clone(),
+			trailing: format.trailing.clone(),
+			..Default::default()
+		});
+	}
+	*existing = entry;
+}
+
+fn patch_entries(existing: &mut kdl::KdlNode, entries: Vec<kdl::KdlEntry>) {
+	let (props, mut args): (Vec<_>, Vec<_>) = entries.into_iter().partition(|entry| entry.name().is_some());
+	let mut props = props.into_iter().map(Some).collect::<Vec<_>>();
+	args.reverse();
+
+	// Positional arguments are matched by position, and properties by name.
+	existing.entries_mut().retain_mut(|entry| {
+		let new_entry = match entry.name() {
+			None => args.pop(),
+			Some(name) => {
+				let name = name.value();
+				let new_prop = props.iter_mut().find(|prop| {
+					prop.as_ref()
+						.is_some_and(|prop| prop.name().is_some_and(|key| key.value() == name))
+				});
+				new_prop.and_then(Option::take)
+			}
+		};
+		match new_entry {
+			None => false,
+			Some(new_entry) => {
+				if !same_entry(entry, &new_entry) {
+					replace_entry(entry, new_entry);
+				}
+				true
+			}
+		}
+	});
+
+	// Arguments and properties which did not exist before are added after the existing ones.
+	let insert_args_at = match existing.entries().iter().rposition(|entry| entry.name().is_none()) {
+		Some(idx) => idx + 1,
+		None => 0,
+	};
+	for (offset, arg) in args.into_iter().rev().enumerate() {
+		existing.entries_mut().insert(insert_args_at + offset, arg);
+	}
+	existing.entries_mut().extend(props.into_iter().flatten());
+}
+
+/// Patches the nodes of a document, where `parent_indent` is the indentation of the node which owns it (if any).
+fn patch_nodes(doc: &mut kdl::KdlDocument, nodes: Vec<kdl::KdlNode>, parent_indent: Option<&str>) {
+	let indent = match (doc.nodes().first(), parent_indent) {
+		(Some(sibling), _) => leading_indent(sibling.format().map(|format| format.leading.as_str())),
+		(None, Some(parent_indent)) => format!("{parent_indent}{}", indent_unit(parent_indent)),
+		(None, None) => String::new(),
+	};
+
+	let mut nodes = nodes.into_iter().map(Some).collect::<Vec<_>>();
+	// Each existing node is patched by the next unmatched new node with the same name, or removed.
+	// The line break before a removed node is kept, so that the next node stays on its own line.
+	let mut matched = Vec::with_capacity(nodes.len());
+	let mut line_breaks = String::new();
+	doc.nodes_mut().retain_mut(|existing| {
+		let name = existing.name().value();
+		let found = nodes
+			.iter_mut()
+			.enumerate()
+			.find(|(_, node)| node.as_ref().is_some_and(|node| node.name().value() == name));
+		let Some((idx, Some(node))) = found.map(|(idx, node)| (idx, node.take())) else {
+			if let Some(format) = existing.format_mut() {
+				line_breaks.push_str(&take_line_break(&mut format.leading));
+			}
+			return false;
+		};
+		patch_node(existing, node);
+		if let Some(format) = existing.format_mut() {
+			format.leading.insert_str(0, &std::mem::take(&mut line_breaks));
+		}
+		matched.push(idx);
+		true
+	});
+
+	// New nodes are inserted after the existing node which preceded them in the new document.
+	let mut positions = matched;
+	for (idx, node) in nodes.into_iter().enumerate() {
+		let Some(mut node) = node else { continue };
+		format_inserted(&mut node, &indent);
+		let insert_at = positions.iter().filter(|prev| **prev < idx).count();
+		let line_break = match doc.nodes_mut().first_mut().and_then(kdl::KdlNode::format_mut) {
+			Some(format) if insert_at == 0 => take_line_break(&mut format.leading),
+			None if insert_at == 0 => std::mem::take(&mut line_breaks),
+			_ => String::new(),
+		};
+		if let Some(format) = node.format_mut() {
+			format.leading.insert_str(0, &line_break);
+		}
+		doc.nodes_mut().insert(insert_at, node);
+		positions.insert(insert_at, idx);
+	}
+}
+
+/// Removes the whitespace up to and including the first line break at the start of a node's leading text.
+fn take_line_break(leading: &mut String) -> String {
+	match leading.find('\n') {
+		Some(idx) if leading[..idx].chars().all(char::is_whitespace) => leading.drain(..=idx).collect(),
+		_ => String::new(),
+	}
+}
+
+fn patch_node(existing: &mut kdl::KdlNode, node: kdl::KdlNode) {
+	let builder = Node {
+		entries: node.entries().to_vec(),
+		children: node.children().map(|doc| doc.nodes().to_vec()).unwrap_or_default(),
+		comments: Vec::new(),
+	};
+	builder.patch(existing);
+}
+
+/// A guess at the whitespace used for each level of indentation, based on a line's indentation.
+fn indent_unit(indent: &str) -> &'static str {
+	match indent {
+		"" => "    ",
+		indent if indent.starts_with('\t') => "\t",
+		indent if indent.len() % 4 == 0 => "    ",
+		indent if indent.len() % 2 == 0 => "  ",
+		_ => " ",
+	}
+}
+
+fn format_inserted(node: &mut kdl::KdlNode, indent: &str) {
+	let unit = indent_unit(indent);
+	if node.format().is_none() {
+		node.set_format(kdl::KdlNodeFormat::default());
+	}
+	node.autoformat_config(
+		&kdl::FormatConfig::builder()
+			.indent_level(indent.len() / unit.len())
+			.indent(unit)
+			.build(),
+	);
+}
+
+/// The whitespace at the start of the last line of a node's leading text, i.e. its indentation.
+fn leading_indent(leading: Option<&str>) -> String {
+	let leading = leading.unwrap_or_default();
+	let line = leading.rsplit('\n').next().unwrap_or_default();
+	line.chars().take_while(|ch| ch.is_whitespace()).collect()
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::builder::{Child, Property, Value};
+
+	fn patched(src: &str, node: Node) -> String {
+		let mut doc = src.parse::<kdl::KdlDocument>().expect("valid kdl");
+		node.patch_document(&mut doc);
+		doc.to_string()
+	}
+
+	#[test]
+	fn unchanged() {
+		let src = "// the server\nserver  \"localhost\" port=80 /* http */ {\n\ttls #false // for now\n}\n";
+		let node = Node::default().with(Child(
+			"server",
+			Node::default()
+				.with(Value("localhost"))
+				.with(Property("port", Value(80)))
+				.with(Child("tls", Value(false))),
+		));
+		assert_eq!(patched(src, node), src);
+	}
+
+	#[test]
+	fn changed_values() {
+		let src = "// the server\nserver  \"localhost\" port=80 /* http */ {\n\ttls #false // for now\n}\n";
+		let node = Node::default().with(Child(
+			"server",
+			Node::default()
+				.with(Value("example.com"))
+				.with(Property("port", Value(443)))
+				.with(Child("tls", Value(true))),
+		));
+		let expected = "// the server\nserver  example.com port=443 /* http */ {\n\ttls #true // for now\n}\n";
+		assert_eq!(patched(src, node), expected);
+	}
+
+	#[test]
+	fn added_and_removed() {
+		let src = "// the server\nserver \"localhost\" port=80 {\n\t// old\n\tlegacy #true\n\ttls #false\n}\n";
+		let node = Node::default().with(Child(
+			"server",
+			Node::default()
+				.with(Value("localhost"))
+				.with(Property("timeout", Value(30)))
+				.with(Child("tls", Value(false)))
+				.with(Child("cert", Value("cert.pem"))),
+		));
+		let expected = "// the server\nserver \"localhost\" timeout=30 {\n\ttls #false\n\tcert cert.pem\n}\n";
+		assert_eq!(patched(src, node), expected);
+	}
+
+	#[test]
+	fn inserted_in_order() {
+		let src = "first 1\n\n// last\nlast 3\n";
+		let node = Node::default()
+			.with(Child("zeroth", Value(0)))
+			.with(Child("first", Value(1)))
+			.with(Child("second", Value(2)))
+			.with(Child("last", Value(3)));
+		let expected = "zeroth 0\nfirst 1\nsecond 2\n\n// last\nlast 3\n";
+		assert_eq!(patched(src, node), expected);
+	}
+
+	#[test]
+	fn new_children() {
+		let src = "name \"app\" // keep\nserver \"localhost\"\n";
+		let node = Node::default()
+			.with(Child("name", Value("app")))
+			.with(Child(
+				"server",
+				Node::default().with(Value("localhost")).with(Child("tls", Value(true))),
+			))
+			.with(Child("debug", Value(true)));
+		let expected = "name \"app\" // keep\nserver \"localhost\" {\n    tls #true\n}\ndebug #true\n";
+		assert_eq!(patched(src, node), expected);
+	}
+}
@@ -0,0 +1,285 @@
+//! Values for the date and time types of `chrono` and `time`, which are written as strings with the
+//! type annotations reserved by KDL: `(date-time)` for RFC 3339 date-times, `(date)` and `(time)`.
+//! Values without an annotation are also read, but values with any other annotation are not.
+
+const DATE_TIME: &str = "date-time";
+const DATE: &str = "date";
+const TIME: &str = "time";
+
+macro_rules! impl_kdlvalue_datetime {
+	($target:ty, $annotation:expr, $format:expr, $parse:expr, $write:expr) => {
+		impl<'doc> $crate::FromKdlValue<'doc> for $target {
+			type Error = $crate::error::InvalidString;
+			fn from_kdl(value: &'doc kdl::KdlValue) -> Result<Self, Self::Error> {
+				$crate::parse_str(value, $format, $parse)
+			}
+
+			fn type_annotation() -> Option<&'static str> {
+				Some($annotation)
+			}
+		}
+		impl $crate::AsKdlValue for $target {
+			fn as_kdl(&self) -> kdl::KdlValue {
+				kdl::KdlValue::String($write(self))
+			}
+
+			fn type_annotation(&self) -> Option<&'static str> {
+				Some($annotation)
+			}
+		}
+	};
+}
+
+#[cfg(feature = "chrono")]
+mod chrono {
+	use super::{DATE, DATE_TIME, TIME};
+	use ::chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, ParseResult, SecondsFormat, Utc};
+
+	/// Parses an RFC 3339 date-time, or one with a year outside 0000-9999 as written by `to_rfc3339_opts`.
+	fn parse_date_time(text: &str) -> ParseResult<DateTime<FixedOffset>> {
+		DateTime::parse_from_rfc3339(text).or_else(|err| {
+			// `%:z` does not accept `Z`, which is how UTC is written.
+			let text = match text.strip_suffix('Z') {
+				Some(date_time) => format!("{date_time}+00:00"),
+				None => text.to_owned(),
+			};
+			DateTime::parse_from_str(&text, "%Y-%m-%dT%H:%M:%S%.f%:z").map_err(|_| err)
+		})
+	}
+
+	impl_kdlvalue_datetime!(
+		DateTime<Utc>,
+		DATE_TIME,
+		"an RFC 3339 date-time, e.g. 2024-01-31T12:00:00Z",
+		|text| parse_date_time(text).map(|value| value.to_utc()),
+		|value: &DateTime<Utc>| value.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+	);
+	impl_kdlvalue_datetime!(
+		DateTime<FixedOffset>,
+		DATE_TIME,
+		"an RFC 3339 date-time, e.g. 2024-01-31T12:00:00+02:00",
+		parse_date_time,
+		|value: &DateTime<FixedOffset>| value.to_rfc3339_opts(SecondsFormat::AutoSi, false)
+	);
+	impl_kdlvalue_datetime!(
+		NaiveDateTime,
+		DATE_TIME,
+		"a date-time without an offset, e.g. 2024-01-31T12:00:00",
+		str::parse::<NaiveDateTime>,
+		|value: &NaiveDateTime| value.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+	);
+	impl_kdlvalue_datetime!(
+		NaiveDate,
+		DATE,
+		"a date, e.g. 2024-01-31",
+		str::parse::<NaiveDate>,
+		NaiveDate::to_string
+	);
+	impl_kdlvalue_datetime!(
+		NaiveTime,
+		TIME,
+		"a time, e.g. 12:00:00",
+		str::parse::<NaiveTime>,
+		NaiveTime::to_string
+	);
+}
+
+#[cfg(feature = "time")]
+mod time {
+	use super::{DATE, DATE_TIME, TIME};
+	use ::time::{
+		format_description::{well_known::Rfc3339, BorrowedFormatItem},
+		macros::format_description,
+		Date, OffsetDateTime, PrimitiveDateTime, Time,
+	};
+
+	const DATE_FORMAT: &[BorrowedFormatItem<'_>] = format_description!("[year]-[month]-[day]");
+	// Subseconds are optional when parsing, and only written when they are not zero.
+	const TIME_FORMAT: &[BorrowedFormatItem<'_>] =
+		format_description!("[hour]:[minute]:[second][optional [.[subsecond]]]");
+	const WHOLE_TIME_FORMAT: &[BorrowedFormatItem<'_>] = format_description!("[hour]:[minute]:[second]");
+	const DATE_TIME_FORMAT: &[BorrowedFormatItem<'_>] =
+		format_description!("[year]-[month]-[day]T[hour]:[minute]:[second][optional [.[subsecond]]]");
+	const WHOLE_DATE_TIME_FORMAT: &[BorrowedFormatItem<'_>] =
+		format_description!("[year]-[month]-[day]T[hour]:[minute]:[second]");
+
+	// Date-times written by `format_offset_date_time` which RFC 3339 cannot represent.
+	const EXTENDED_DATE_TIME_FORMAT: &[BorrowedFormatItem<'_>] = format_description!(
+		"[year]-[month]-[day]T[hour]:[minute]:[second][optional [.[subsecond]]][offset_hour sign:mandatory]:[offset_minute][optional [:[offset_second]]]"
+	);
+	const OFFSET_FORMAT: &[BorrowedFormatItem<'_>] =
+		format_description!("[offset_hour sign:mandatory]:[offset_minute]:[offset_second]");
+
+	/// Formats a value which can only fail to format if the format needs a component the value does not have.
+	fn format(value: Result<String, ::time::error::Format>) -> String {
+		value.expect("format only uses components of the value")
+	}
+
+	/// Formats an RFC 3339 date-time, unless the year is outside 0000-9999 or the offset has seconds,
+	/// which are written in the same layout with a signed year and an offset with seconds.
+	fn format_offset_date_time(value: &OffsetDateTime) -> String {
+		value.format(&Rfc3339).unwrap_or_else(|_| {
+			let date_time = match value.nanosecond() {
+				0 => format(value.format(WHOLE_DATE_TIME_FORMAT)),
+				_ => format(value.format(DATE_TIME_FORMAT)),
+			};
+			date_time + &format(value.format(OFFSET_FORMAT))
+		})
+	}
+
+	impl_kdlvalue_datetime!(
+		OffsetDateTime,
+		DATE_TIME,
+		"an RFC 3339 date-time, e.g. 2024-01-31T12:00:00Z",
+		|text| OffsetDateTime::parse(text, &Rfc3339)
+			.or_else(|_| OffsetDateTime::parse(text, EXTENDED_DATE_TIME_FORMAT)),
+		format_offset_date_time
+	);
+	impl_kdlvalue_datetime!(
+		PrimitiveDateTime,
+		DATE_TIME,
+		"a date-time without an offset, e.g. 2024-01-31T12:00:00",
+		|text| PrimitiveDateTime::parse(text, DATE_TIME_FORMAT),
+		|value: &PrimitiveDateTime| match value.nanosecond() {
+			0 => format(value.format(WHOLE_DATE_TIME_FORMAT)),
+			_ => format(value.format(DATE_TIME_FORMAT)),
+		}
+	);
+	impl_kdlvalue_datetime!(
+		Date,
+		DATE,
+		"a date, e.g. 2024-01-31",
+		|text| Date::parse(text, DATE_FORMAT),
+		|value: &Date| format(value.format(DATE_FORMAT))
+	);
+	impl_kdlvalue_datetime!(
+		Time,
+		TIME,
+		"a time, e.g. 12:00:00",
+		|text| Time::parse(text, TIME_FORMAT),
+		|value: &Time| match value.nanosecond() {
+			0 => format(value.format(WHOLE_TIME_FORMAT)),
+			_ => format(value.format(TIME_FORMAT)),
+		}
+	);
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{
+		builder::{Node, Value},
+		reader::EntryExt,
+	};
+
+	fn written<V: crate::AsKdlValue>(value: V) -> String {
+		Node::default()
+			.with(Value(value))
+			.build("node")
+			.to_string()
+			.trim_end()
+			.to_owned()
+	}
+
+	fn entry(src: &str) -> kdl::KdlEntry {
+		let doc = src.parse::<kdl::KdlDocument>().expect("valid kdl");
+		doc.nodes()[0].entries()[0].clone()
+	}
+
+	#[cfg(feature = "chrono")]
+	#[test]
+	fn chrono() {
+		use ::chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+		let date_time = DateTime::parse_from_rfc3339("2024-01-31T12:30:00Z").unwrap().to_utc();
+		assert_eq!(written(date_time), "node (date-time)\"2024-01-31T12:30:00Z\"");
+		assert_eq!(
+			entry("node (date-time)\"2024-01-31T14:30:00+02:00\"").to(),
+			Ok(date_time)
+		);
+		let offset = entry("node \"2024-01-31T14:30:00.5+02:00\"").to::<DateTime<FixedOffset>>();
+		assert_eq!(
+			offset.map(|value| value.to_rfc3339()),
+			Ok("2024-01-31T14:30:00.500+02:00".to_owned())
+		);
+
+		let date = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+		assert_eq!(written(date), "node (date)\"2024-01-31\"");
+		assert_eq!(entry("node (date)\"2024-01-31\"").to(), Ok(date));
+		let time = NaiveTime::from_hms_opt(12, 30, 0).unwrap();
+		assert_eq!(written(time), "node (time)\"12:30:00\"");
+		assert_eq!(entry("node \"12:30:00\"").to(), Ok(time));
+		assert_eq!(written(date.and_time(time)), "node (date-time)\"2024-01-31T12:30:00\"");
+		assert_eq!(entry("node \"2024-01-31T12:30:00\"").to(), Ok(date.and_time(time)));
+		assert!(entry("node \"2024-01-31T12:30:00\"").to::<NaiveDate>().is_err());
+		assert!(entry("node \"yesterday\"").to::<NaiveDateTime>().is_err());
+
+		// Years outside of RFC 3339 are written with a sign, and read back.
+		let distant = NaiveDate::from_ymd_opt(10000, 1, 1)
+			.unwrap()
+			.and_time(NaiveTime::MIN)
+			.and_utc();
+		assert_eq!(written(distant), "node (date-time)\"+10000-01-01T00:00:00Z\"");
+		assert_eq!(entry(&written(distant)).to(), Ok(distant));
+		let distant = distant.fixed_offset();
+		assert_eq!(written(distant), "node (date-time)\"+10000-01-01T00:00:00+00:00\"");
+		assert_eq!(entry(&written(distant)).to(), Ok(distant));
+		let ancient = NaiveDate::from_ymd_opt(-44, 3, 15)
+			.unwrap()
+			.and_time(NaiveTime::MIN)
+			.and_utc();
+		assert_eq!(entry(&written(ancient)).to(), Ok(ancient));
+	}
+
+	#[cfg(feature = "time")]
+	#[test]
+	fn time() {
+		use ::time::macros::{date, datetime, time};
+		let date_time = datetime!(2024-01-31 12:30:00 UTC);
+		assert_eq!(written(date_time), "node (date-time)\"2024-01-31T12:30:00Z\"");
+		assert_eq!(
+			entry("node (date-time)\"2024-01-31T14:30:00+02:00\"").to(),
+			Ok(date_time)
+		);
+
+		assert_eq!(written(date!(2024 - 01 - 31)), "node (date)\"2024-01-31\"");
+		assert_eq!(entry("node (date)\"2024-01-31\"").to(), Ok(date!(2024 - 01 - 31)));
+		assert_eq!(written(time!(12:30:00)), "node (time)\"12:30:00\"");
+		assert_eq!(written(time!(12:30:00.25)), "node (time)\"12:30:00.25\"");
+		assert_eq!(entry("node \"12:30:00.25\"").to(), Ok(time!(12:30:00.25)));
+		let primitive = datetime!(2024-01-31 12:30:00);
+		assert_eq!(written(primitive), "node (date-time)\"2024-01-31T12:30:00\"");
+		assert_eq!(entry("node \"2024-01-31T12:30:00\"").to(), Ok(primitive));
+		assert!(entry("node 20240131").to::<::time::Date>().is_err());
+
+		// Values outside of RFC 3339 are written in a layout which is read back.
+		let ancient = datetime!(-0044-03-15 12:00:00 UTC);
+		assert_eq!(written(ancient), "node (date-time)\"-0044-03-15T12:00:00+00:00:00\"");
+		assert_eq!(entry(&written(ancient)).to(), Ok(ancient));
+		let offset = datetime!(2024-01-31 12:30:00 +01:02:03);
+		assert_eq!(written(offset), "node (date-time)\"2024-01-31T12:30:00+01:02:03\"");
+		assert_eq!(entry(&written(offset)).to(), Ok(offset));
+	}
+
+	#[cfg(feature = "chrono")]
+	#[test]
+	fn annotation_mismatch() {
+		use crate::{builder::Typed, error::TypeAnnotationMismatch};
+		use ::chrono::NaiveDate;
+		let err = entry("node (time)\"2024-01-31\"")
+			.to::<NaiveDate>()
+			.expect_err("wrong annotation");
+		let label = miette::Diagnostic::labels(&err).and_then(|mut labels| labels.next());
+		let mismatch = TypeAnnotationMismatch {
+			expected: "date",
+			found: "time".to_owned(),
+		};
+		assert_eq!(
+			label.and_then(|label| label.label().map(str::to_owned)),
+			Some(mismatch.to_string())
+		);
+
+		// An explicit annotation replaces the reserved one.
+		let date = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+		let node = Node::default().with(Typed("birthday", Value(date))).build("node");
+		assert_eq!(node.to_string(), "node (birthday)\"2024-01-31\"\n");
+	}
+}
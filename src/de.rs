@@ -0,0 +1,634 @@
+//! A [`serde::Deserializer`] for KDL documents and nodes, enabled by the `serde` feature.
+//!
+//! Types which implement `serde::Deserialize` are read from KDL using the following mapping:
+//! - A document is a map (or struct) whose keys are the names of its nodes.
+//! - A node is a map (or struct) whose keys are its property names and the names of its children.
+//!   When several children share a name, the value for that key is a sequence of those children.
+//! - A node is a sequence of its positional arguments followed by its children.
+//!   A node without positional arguments is instead a single-element sequence of itself if it has properties
+//!   or children other than unnamed `-` children, so that one struct-like child is still read as one element.
+//! - A node with exactly one positional argument (and nothing else) is a primitive, i.e. that argument.
+//! - Enums are either a string argument naming a unit variant (`mode "fast"`),
+//!   or a single child node named after the variant (`shape { circle 5.0; }`).
+//! - A missing key is `None` for `Option` fields, and a `#null` argument is `None` / `()`.
+//!
+//! Errors carry the span of the entry or node which failed to deserialize.
+
+use serde::de::{self, IntoDeserializer, Visitor};
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
+#[error("{message}")]
+#[diagnostic(code(kdlize::deserialize))]
+pub struct Error {
+	message: String,
+	#[label("{message}")]
+	span: Option<miette::SourceSpan>,
+}
+impl Error {
+	pub fn span(&self) -> Option<miette::SourceSpan> {
+		self.span
+	}
+
+	fn with_span(mut self, span: miette::SourceSpan) -> Self {
+		if self.span.is_none() {
+			self.span = Some(span);
+		}
+		self
+	}
+}
+impl de::Error for Error {
+	fn custom<T: std::fmt::Display>(msg: T) -> Self {
+		Self {
+			message: msg.to_string(),
+			span: None,
+		}
+	}
+}
+
+/// Deserializes a value from the nodes of a document.
+pub fn from_document<'de, T: de::Deserialize<'de>>(doc: &'de kdl::KdlDocument) -> Result<T, Error> {
+	T::deserialize(DocumentDeserializer(doc))
+}
+
+/// Deserializes a value from a single node.
+pub fn from_node<'de, T: de::Deserialize<'de>>(node: &'de kdl::KdlNode) -> Result<T, Error> {
+	T::deserialize(NodeDeserializer(node))
+}
+
+impl<'doc, Context> crate::reader::Node<'doc, Context> {
+	/// Deserializes a value from this node using its `serde::Deserialize` impl,
+	/// which marks all of its entries and children as read.
+	pub fn deserialize<T: de::Deserialize<'doc>>(&self) -> Result<T, Error> {
+		self.entries();
+		let document = self.document();
+		match self.node {
+			Some(node) => from_node(node),
+			None => from_document(document.map_err(de::Error::custom)?),
+		}
+	}
+}
+
+pub struct DocumentDeserializer<'de>(pub &'de kdl::KdlDocument);
+impl<'de> de::Deserializer<'de> for DocumentDeserializer<'de> {
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		let map = MapAccess::new(&[], self.0.nodes());
+		visitor.visit_map(map).map_err(|err| err.with_span(self.0.span()))
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_some(self)
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	serde::forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf unit unit_struct seq tuple
+		tuple_struct map struct enum identifier ignored_any
+	}
+}
+
+pub struct NodeDeserializer<'de>(pub &'de kdl::KdlNode);
+impl<'de> NodeDeserializer<'de> {
+	fn args(&self) -> impl Iterator<Item = &'de kdl::KdlEntry> {
+		self.0.entries().iter().filter(|entry| entry.name().is_none())
+	}
+
+	fn props(&self) -> Vec<&'de kdl::KdlEntry> {
+		self.0.entries().iter().filter(|entry| entry.name().is_some()).collect()
+	}
+
+	fn children(&self) -> &'de [kdl::KdlNode] {
+		self.0.children().map(kdl::KdlDocument::nodes).unwrap_or_default()
+	}
+
+	fn single_arg(&self) -> Option<&'de kdl::KdlEntry> {
+		let mut args = self.args();
+		let arg = args.next()?;
+		let only_arg = args.next().is_none() && self.props().is_empty() && self.children().is_empty();
+		only_arg.then_some(arg)
+	}
+
+	/// Whether the node is a single element of a sequence, rather than a sequence of its arguments and children.
+	fn is_element(&self) -> bool {
+		let unnamed_children = self.children().iter().all(|child| child.name().value() == "-");
+		self.args().next().is_none() && (!self.props().is_empty() || !unnamed_children)
+	}
+
+	fn visit_seq<V: Visitor<'de>>(&self, visitor: V) -> Result<V::Value, Error> {
+		let elements = match self.is_element() {
+			true => vec![Element::Node(self.0)],
+			false => {
+				let args = self.args().map(Element::Entry);
+				let children = self.children().iter().map(Element::Node);
+				args.chain(children).collect()
+			}
+		};
+		visitor.visit_seq(SeqAccess(elements.into_iter()))
+	}
+}
+impl<'de> de::Deserializer<'de> for NodeDeserializer<'de> {
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		let span = self.0.span();
+		let result = if let Some(arg) = self.single_arg() {
+			EntryDeserializer(arg).deserialize_any(visitor)
+		} else if !self.props().is_empty() || !self.children().is_empty() {
+			visitor.visit_map(MapAccess::new(&self.props(), self.children()))
+		} else if self.args().next().is_some() {
+			self.visit_seq(visitor)
+		} else {
+			visitor.visit_unit()
+		};
+		result.map_err(|err| err.with_span(span))
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		match self.single_arg() {
+			Some(arg) if arg.value().is_null() => visitor.visit_none(),
+			_ => visitor.visit_some(self),
+		}
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.visit_seq(visitor).map_err(|err| err.with_span(self.0.span()))
+	}
+
+	fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+		self.deserialize_seq(visitor)
+	}
+
+	fn deserialize_tuple_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_len: usize,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.deserialize_seq(visitor)
+	}
+
+	fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		let map = MapAccess::new(&self.props(), self.children());
+		visitor.visit_map(map).map_err(|err| err.with_span(self.0.span()))
+	}
+
+	fn deserialize_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.deserialize_map(visitor)
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		let span = self.0.span();
+		let result = match (self.single_arg(), self.children()) {
+			(Some(arg), _) => EntryDeserializer(arg).deserialize_enum(name, variants, visitor),
+			(None, [child]) if self.args().next().is_none() && self.props().is_empty() => {
+				visitor.visit_enum(VariantAccess(child))
+			}
+			_ => Err(de::Error::custom(format!(
+				"expected a string argument or a single child node naming a variant of {name}"
+			))),
+		};
+		result.map_err(|err| err.with_span(span))
+	}
+
+	serde::forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf unit unit_struct identifier ignored_any
+	}
+}
+
+pub struct EntryDeserializer<'de>(pub &'de kdl::KdlEntry);
+impl<'de> de::Deserializer<'de> for EntryDeserializer<'de> {
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		let result: Result<V::Value, Error> = match self.0.value() {
+			kdl::KdlValue::String(value) => visitor.visit_borrowed_str(value.as_str()),
+			kdl::KdlValue::Integer(value) => {
+				let value = *value;
+				if let Ok(value) = i64::try_from(value) {
+					visitor.visit_i64(value)
+				} else if let Ok(value) = u64::try_from(value) {
+					visitor.visit_u64(value)
+				} else {
+					visitor.visit_i128(value)
+				}
+			}
+			kdl::KdlValue::Float(value) => visitor.visit_f64(*value),
+			kdl::KdlValue::Bool(value) => visitor.visit_bool(*value),
+			kdl::KdlValue::Null => visitor.visit_unit(),
+		};
+		result.map_err(|err| err.with_span(self.0.span()))
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		match self.0.value() {
+			kdl::KdlValue::Null => visitor.visit_none(),
+			_ => visitor.visit_some(self),
+		}
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		let span = self.0.span();
+		let result = match self.0.value() {
+			kdl::KdlValue::String(value) => visitor.visit_enum(value.as_str().into_deserializer()),
+			_ => self.deserialize_any(visitor),
+		};
+		result.map_err(|err: Error| err.with_span(span))
+	}
+
+	serde::forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf unit unit_struct seq tuple
+		tuple_struct map struct identifier ignored_any
+	}
+}
+
+/// A value in a sequence, which is either a positional argument or a child node.
+enum Element<'de> {
+	Entry(&'de kdl::KdlEntry),
+	Node(&'de kdl::KdlNode),
+}
+
+struct SeqAccess<I>(I);
+impl<'de, I: Iterator<Item = Element<'de>>> de::SeqAccess<'de> for SeqAccess<I> {
+	type Error = Error;
+
+	fn next_element_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+		match self.0.next() {
+			None => Ok(None),
+			Some(Element::Entry(entry)) => seed.deserialize(EntryDeserializer(entry)).map(Some),
+			Some(Element::Node(node)) => seed.deserialize(NodeDeserializer(node)).map(Some),
+		}
+	}
+}
+
+/// The value of a key in a map, which is either a property or all of the child nodes with the key as their name.
+enum MapValue<'de> {
+	Prop(&'de kdl::KdlEntry),
+	Children(Vec<&'de kdl::KdlNode>),
+}
+
+struct MapAccess<'de> {
+	entries: std::vec::IntoIter<(&'de str, MapValue<'de>)>,
+	value: Option<MapValue<'de>>,
+}
+impl<'de> MapAccess<'de> {
+	fn new(props: &[&'de kdl::KdlEntry], children: &'de [kdl::KdlNode]) -> Self {
+		let mut entries = Vec::with_capacity(props.len() + children.len());
+		for prop in props {
+			let name = prop.name().map(kdl::KdlIdentifier::value).unwrap_or_default();
+			entries.push((name, MapValue::Prop(prop)));
+		}
+		let mut groups: Vec<(&'de str, MapValue<'de>)> = Vec::new();
+		for child in children {
+			let name = child.name().value();
+			match groups.iter_mut().find(|(group_name, _)| *group_name == name) {
+				Some((_, MapValue::Children(nodes))) => nodes.push(child),
+				_ => groups.push((name, MapValue::Children(vec![child]))),
+			}
+		}
+		entries.extend(groups);
+		Self {
+			entries: entries.into_iter(),
+			value: None,
+		}
+	}
+}
+impl<'de> de::MapAccess<'de> for MapAccess<'de> {
+	type Error = Error;
+
+	fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+		let Some((key, value)) = self.entries.next() else {
+			return Ok(None);
+		};
+		self.value = Some(value);
+		seed.deserialize(de::value::BorrowedStrDeserializer::new(key)).map(Some)
+	}
+
+	fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+		match self.value.take() {
+			Some(MapValue::Prop(entry)) => seed.deserialize(EntryDeserializer(entry)),
+			Some(MapValue::Children(nodes)) => seed.deserialize(ChildrenDeserializer(nodes)),
+			None => Err(de::Error::custom("value requested before key")),
+		}
+	}
+}
+
+/// All of the child nodes which share a name, which are deserialized as the single node if there is only one,
+/// or otherwise as a sequence of nodes.
+struct ChildrenDeserializer<'de>(Vec<&'de kdl::KdlNode>);
+impl<'de> de::Deserializer<'de> for ChildrenDeserializer<'de> {
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		match self.0.as_slice() {
+			[node] => NodeDeserializer(node).deserialize_any(visitor),
+			_ => self.deserialize_seq(visitor),
+		}
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		match self.0.as_slice() {
+			[node] => NodeDeserializer(node).deserialize_option(visitor),
+			_ => visitor.visit_some(self),
+		}
+	}
+
+	fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		match self.0.as_slice() {
+			[node] => NodeDeserializer(node).deserialize_seq(visitor),
+			_ => visitor.visit_seq(SeqAccess(self.0.into_iter().map(Element::Node))),
+		}
+	}
+
+	fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+		self.deserialize_seq(visitor)
+	}
+
+	fn deserialize_tuple_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_len: usize,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.deserialize_seq(visitor)
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		let node = self.last()?;
+		NodeDeserializer(node).deserialize_map(visitor)
+	}
+
+	fn deserialize_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		let node = self.last()?;
+		NodeDeserializer(node).deserialize_struct(name, fields, visitor)
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		let node = self.last()?;
+		NodeDeserializer(node).deserialize_enum(name, variants, visitor)
+	}
+
+	serde::forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf unit unit_struct identifier ignored_any
+	}
+}
+impl<'de> ChildrenDeserializer<'de> {
+	/// Like properties, later nodes override earlier ones when a single value is expected.
+	fn last(&self) -> Result<&'de kdl::KdlNode, Error> {
+		self.0.last().copied().ok_or_else(|| de::Error::custom("missing node"))
+	}
+}
+
+/// Accesses the variant of an enum which is represented by a child node named after the variant.
+struct VariantAccess<'de>(&'de kdl::KdlNode);
+impl<'de> de::EnumAccess<'de> for VariantAccess<'de> {
+	type Error = Error;
+	type Variant = Self;
+
+	fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+		let name = self.0.name().value();
+		let variant = seed.deserialize(de::value::BorrowedStrDeserializer::new(name))?;
+		Ok((variant, self))
+	}
+}
+impl<'de> de::VariantAccess<'de> for VariantAccess<'de> {
+	type Error = Error;
+
+	fn unit_variant(self) -> Result<(), Error> {
+		Ok(())
+	}
+
+	fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+		seed.deserialize(NodeDeserializer(self.0))
+	}
+
+	fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+		de::Deserializer::deserialize_seq(NodeDeserializer(self.0), visitor)
+	}
+
+	fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+		de::Deserializer::deserialize_map(NodeDeserializer(self.0), visitor)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use serde::Deserialize;
+
+	#[derive(Deserialize, Debug, PartialEq)]
+	struct Config {
+		name: String,
+		port: u16,
+		debug: Option<bool>,
+		tags: Vec<String>,
+		server: Vec<Server>,
+		mode: Mode,
+		shape: Shape,
+	}
+
+	#[derive(Deserialize, Debug, PartialEq)]
+	struct Server {
+		host: String,
+		weight: Option<f32>,
+	}
+
+	#[derive(Deserialize, Debug, PartialEq)]
+	#[serde(rename_all = "lowercase")]
+	enum Mode {
+		Fast,
+		Safe,
+	}
+
+	#[derive(Deserialize, Debug, PartialEq)]
+	#[serde(rename_all = "lowercase")]
+	enum Shape {
+		Circle(f64),
+		Rect { width: u32, height: u32 },
+	}
+
+	fn parse(src: &str) -> kdl::KdlDocument {
+		src.parse().expect("valid kdl")
+	}
+
+	#[test]
+	fn document_struct() -> Result<(), Error> {
+		let doc = parse(
+			r#"
+			name "example"
+			port 8080
+			tags "a" "b" "c"
+			server host="one" weight=0.5
+			server host="two"
+			mode "safe"
+			shape { rect width=2 height=3; }
+			"#,
+		);
+		let config = from_document::<Config>(&doc)?;
+		assert_eq!(
+			config,
+			Config {
+				name: "example".into(),
+				port: 8080,
+				debug: None,
+				tags: vec!["a".into(), "b".into(), "c".into()],
+				server: vec![
+					Server {
+						host: "one".into(),
+						weight: Some(0.5),
+					},
+					Server {
+						host: "two".into(),
+						weight: None,
+					},
+				],
+				mode: Mode::Safe,
+				shape: Shape::Rect { width: 2, height: 3 },
+			}
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn single_struct_child_is_one_element() -> Result<(), Error> {
+		#[derive(Deserialize, Debug, PartialEq)]
+		struct Servers {
+			server: Vec<Server>,
+		}
+		let doc = parse(r#"server host="one""#);
+		let servers = from_document::<Servers>(&doc)?;
+		assert_eq!(servers.server.len(), 1);
+
+		#[derive(Deserialize, Debug, PartialEq)]
+		struct Leaf {
+			v: u32,
+		}
+		#[derive(Deserialize, Debug, PartialEq)]
+		struct Item {
+			leaf: Leaf,
+		}
+		#[derive(Deserialize, Debug, PartialEq)]
+		struct Items {
+			items: Vec<Item>,
+		}
+		// A node with only children is one element too, unless its children are unnamed elements.
+		let doc = parse("items { leaf v=0; }");
+		let items = from_document::<Items>(&doc)?;
+		assert_eq!(items.items, [Item { leaf: Leaf { v: 0 } }]);
+		let doc = parse("items { - { leaf v=0; }; - { leaf v=1; }; }");
+		let items = from_document::<Items>(&doc)?;
+		assert_eq!(items.items.len(), 2);
+		Ok(())
+	}
+
+	#[test]
+	fn node_via_reader() -> Result<(), Error> {
+		let doc = parse(r#"server host="local" { weight 2.0; }"#);
+		let reader = crate::reader::Node::new(&doc.nodes()[0], &());
+		let server = reader.deserialize::<Server>()?;
+		assert_eq!(
+			server,
+			Server {
+				host: "local".into(),
+				weight: Some(2.0),
+			}
+		);
+		// Deserializing marks the entries and children of the node as read.
+		assert!(reader.unconsumed().is_none());
+		Ok(())
+	}
+
+	#[test]
+	fn document_via_reader() -> Result<(), Error> {
+		struct Deserialized(Server);
+		impl<'doc> crate::FromKdlNode<'doc, ()> for Deserialized {
+			type Error = Error;
+			fn from_kdl(node: &mut crate::reader::Node<'doc, ()>) -> Result<Self, Self::Error> {
+				node.deserialize().map(Self)
+			}
+		}
+
+		let doc = parse(r#"host "local"; weight 2.0"#);
+		let mut reader = crate::reader::Document::new(&doc, &());
+		let Deserialized(server) = reader.to::<Deserialized>()?;
+		assert_eq!(server.host, "local");
+		assert!(reader.unconsumed().is_none());
+		Ok(())
+	}
+
+	#[test]
+	fn newtype_variant() -> Result<(), Error> {
+		let doc = parse("shape { circle 1.5; }");
+		let shape = from_node::<Shape>(&doc.nodes()[0])?;
+		assert_eq!(shape, Shape::Circle(1.5));
+		Ok(())
+	}
+
+	#[test]
+	fn error_span() {
+		let src = "name \"example\"\nport \"eighty\"";
+		let doc = parse(src);
+		#[derive(Deserialize, Debug)]
+		#[allow(dead_code)]
+		struct Partial {
+			name: String,
+			port: u16,
+		}
+		let err = from_document::<Partial>(&doc).unwrap_err();
+		let span = err.span().expect("error has a span");
+		assert_eq!(src[span.offset()..span.offset() + span.len()].trim(), "\"eighty\"");
+	}
+
+	#[test]
+	fn missing_field_has_node_span() {
+		let src = "server weight=1.0";
+		let doc = parse(src);
+		let err = from_node::<Server>(&doc.nodes()[0]).unwrap_err();
+		assert!(err.to_string().contains("missing field `host`"), "{err}");
+		assert_eq!(err.span().map(|span| span.offset()), Some(0));
+	}
+}
@@ -0,0 +1,208 @@
+//! Values for `std::time::Duration`, which are written as a string of amounts and units (e.g. `"1m30s"`),
+//! or as a number of seconds. Numbers may be annotated with another unit, e.g. `(ms)500`.
+
+use crate::{
+	error::{InvalidDuration, MalformedValue, UnknownDurationUnit, ValueTypeMismatch},
+	AsKdlValue, FromKdlValue,
+};
+use std::time::Duration;
+
+/// Each unit of time, from largest to smallest, with its length in nanoseconds.
+const UNITS: [(&str, u128); 7] = [
+	("d", 86_400_000_000_000),
+	("h", 3_600_000_000_000),
+	("m", 60_000_000_000),
+	("s", 1_000_000_000),
+	("ms", 1_000_000),
+	("us", 1_000),
+	("ns", 1),
+];
+const UNIT_NAMES: &[&str] = &["d", "h", "m", "s", "ms", "us", "ns"];
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+const FORMAT: &str = "a duration such as \"1m30s\", or a number of seconds";
+
+fn unit_nanos(unit: &str) -> Result<u128, UnknownDurationUnit> {
+	let unit = match unit {
+		"µs" => "us",
+		unit => unit,
+	};
+	match UNITS.iter().find(|(name, _)| *name == unit) {
+		Some((_, nanos)) => Ok(*nanos),
+		None => Err(UnknownDurationUnit {
+			unit: unit.to_owned(),
+			expected: UNIT_NAMES,
+		}),
+	}
+}
+
+fn malformed(value: impl ToString, reason: impl ToString) -> MalformedValue {
+	MalformedValue {
+		type_name: "Duration",
+		value: value.to_string(),
+		format: FORMAT,
+		reason: reason.to_string(),
+	}
+}
+
+fn from_nanos(value: impl ToString, nanos: u128) -> Result<Duration, MalformedValue> {
+	let secs = u64::try_from(nanos / NANOS_PER_SEC).map_err(|_| malformed(value, "it is too long"))?;
+	Ok(Duration::new(secs, (nanos % NANOS_PER_SEC) as u32))
+}
+
+/// Parses an amount of a unit, e.g. `1.5` of `h`, without losing precision to floating point.
+fn amount_nanos(amount: &str, unit_nanos: u128) -> Option<u128> {
+	let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+	if whole.is_empty() && fraction.is_empty() {
+		return None;
+	}
+	let whole = match whole {
+		"" => 0,
+		whole => whole.parse::<u128>().ok()?,
+	};
+	// Digits smaller than a nanosecond of the largest unit cannot change the result.
+	let fraction = &fraction[..fraction.len().min(18)];
+	let fraction_nanos = match fraction {
+		"" => 0,
+		fraction => fraction.parse::<u128>().ok()? * unit_nanos / 10u128.pow(fraction.len() as u32),
+	};
+	whole.checked_mul(unit_nanos)?.checked_add(fraction_nanos)
+}
+
+fn parse_str(text: &str) -> Result<Duration, InvalidDuration> {
+	let mut rest = text.trim_start();
+	if rest.is_empty() {
+		return Err(malformed(text, "it is empty").into());
+	}
+	let mut total: u128 = 0;
+	while !rest.is_empty() {
+		let amount_len = rest
+			.find(|ch: char| !ch.is_ascii_digit() && ch != '.')
+			.unwrap_or(rest.len());
+		let (amount, after) = rest.split_at(amount_len);
+		let after = after.trim_start();
+		let unit_len = after
+			.find(|ch: char| ch.is_ascii_digit() || ch == '.' || ch.is_whitespace())
+			.unwrap_or(after.len());
+		let (unit, after) = after.split_at(unit_len);
+		if amount.is_empty() {
+			return Err(malformed(text, format!("expected an amount before '{unit}'")).into());
+		}
+		if unit.is_empty() {
+			return Err(malformed(text, format!("expected a unit after '{amount}'")).into());
+		}
+		let nanos = amount_nanos(amount, unit_nanos(unit)?);
+		let nanos = nanos.ok_or_else(|| malformed(text, format!("'{amount}' is not a valid amount")))?;
+		total = total
+			.checked_add(nanos)
+			.ok_or_else(|| malformed(text, "it is too long"))?;
+		rest = after.trim_start();
+	}
+	Ok(from_nanos(text, total)?)
+}
+
+impl<'doc> FromKdlValue<'doc> for Duration {
+	type Error = InvalidDuration;
+	fn from_kdl(value: &'doc kdl::KdlValue) -> Result<Self, Self::Error> {
+		Self::from_kdl_typed(value, None)
+	}
+
+	fn from_kdl_typed(value: &'doc kdl::KdlValue, ty: Option<&'doc str>) -> Result<Self, Self::Error> {
+		match value {
+			kdl::KdlValue::String(text) => parse_str(text),
+			kdl::KdlValue::Integer(amount) => {
+				let nanos = unit_nanos(ty.unwrap_or("s"))?;
+				let amount = u128::try_from(*amount).map_err(|_| malformed(amount, "it is negative"))?;
+				let nanos = amount
+					.checked_mul(nanos)
+					.ok_or_else(|| malformed(amount, "it is too long"))?;
+				Ok(from_nanos(amount, nanos)?)
+			}
+			kdl::KdlValue::Float(amount) => {
+				let nanos = unit_nanos(ty.unwrap_or("s"))?;
+				let secs = amount * (nanos as f64 / NANOS_PER_SEC as f64);
+				Ok(Duration::try_from_secs_f64(secs).map_err(|err| malformed(amount, err))?)
+			}
+			_ => Err(ValueTypeMismatch::new(value, "String").into()),
+		}
+	}
+}
+
+impl AsKdlValue for Duration {
+	/// Writes the duration as each non-zero unit from largest to smallest, e.g. `"1h30m"` or `"1s500ms"`.
+	fn as_kdl(&self) -> kdl::KdlValue {
+		let mut nanos = self.as_nanos();
+		if nanos == 0 {
+			return kdl::KdlValue::String("0s".to_owned());
+		}
+		let mut text = String::new();
+		for (unit, unit_nanos) in UNITS {
+			let amount = nanos / unit_nanos;
+			if amount > 0 {
+				text.push_str(&format!("{amount}{unit}"));
+				nanos %= unit_nanos;
+			}
+		}
+		kdl::KdlValue::String(text)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::reader::EntryExt;
+
+	fn read(src: &str) -> Result<Duration, InvalidDuration> {
+		let doc = format!("timeout {src}").parse::<kdl::KdlDocument>().expect("valid kdl");
+		let entry = &doc.nodes()[0].entries()[0];
+		Duration::from_kdl_typed(entry.value(), entry.ty().map(kdl::KdlIdentifier::value))
+	}
+
+	#[test]
+	fn humanized() {
+		assert_eq!(read("\"1m30s\""), Ok(Duration::from_secs(90)));
+		assert_eq!(read("\"1h 2m 3s\""), Ok(Duration::from_secs(3723)));
+		assert_eq!(read("\"1.5s\""), Ok(Duration::from_millis(1500)));
+		assert_eq!(read("\"250ms\""), Ok(Duration::from_millis(250)));
+		assert_eq!(read("\"2d\""), Ok(Duration::from_secs(172_800)));
+		assert_eq!(read("\"10µs\""), Ok(Duration::from_micros(10)));
+	}
+
+	#[test]
+	fn numbers() {
+		assert_eq!(read("90"), Ok(Duration::from_secs(90)));
+		assert_eq!(read("(ms)500"), Ok(Duration::from_millis(500)));
+		assert_eq!(read("(m)2"), Ok(Duration::from_secs(120)));
+		assert_eq!(read("1.25"), Ok(Duration::from_millis(1250)));
+		assert_eq!(read("(ms)1.5"), Ok(Duration::from_micros(1500)));
+	}
+
+	#[test]
+	fn errors() {
+		assert_eq!(
+			read("\"5 fortnights\""),
+			Err(InvalidDuration::UnknownUnit(UnknownDurationUnit {
+				unit: "fortnights".to_owned(),
+				expected: UNIT_NAMES,
+			}))
+		);
+		assert!(matches!(read("(sec)5"), Err(InvalidDuration::UnknownUnit(_))));
+		assert!(matches!(read("\"5\""), Err(InvalidDuration::Malformed(_))));
+		assert!(matches!(read("\"ms\""), Err(InvalidDuration::Malformed(_))));
+		assert!(matches!(read("\"\""), Err(InvalidDuration::Malformed(_))));
+		assert!(matches!(read("-5"), Err(InvalidDuration::Malformed(_))));
+		assert!(matches!(read("#true"), Err(InvalidDuration::TypeMismatch(_))));
+	}
+
+	#[test]
+	fn canonical() {
+		let write = |duration: Duration| duration.as_kdl();
+		assert_eq!(write(Duration::from_secs(90)), "1m30s".into());
+		assert_eq!(write(Duration::from_millis(1500)), "1s500ms".into());
+		assert_eq!(write(Duration::from_secs(90_000)), "1d1h".into());
+		assert_eq!(write(Duration::ZERO), "0s".into());
+
+		let doc = "timeout (ms)90500".parse::<kdl::KdlDocument>().expect("valid kdl");
+		let duration = doc.nodes()[0].entries()[0].to::<Duration>().expect("valid duration");
+		assert_eq!(read(&duration.as_kdl().to_string()), Ok(duration));
+	}
+}
@@ -1,13 +1,31 @@
-#[derive(thiserror::Error, Debug, PartialEq)]
+use crate::reader::NodePath;
+
+#[derive(thiserror::Error, Debug, PartialEq, miette::Diagnostic)]
 pub enum QueryError {
 	#[error(transparent)]
+	#[diagnostic(transparent)]
 	MissingValue(#[from] MissingEntry),
 	#[error(transparent)]
+	#[diagnostic(transparent)]
 	MissingType(#[from] MissingEntryType),
 	#[error(transparent)]
+	#[diagnostic(transparent)]
 	ValueTypeMismatch(#[from] ValueTypeMismatch),
 	#[error(transparent)]
+	#[diagnostic(transparent)]
 	MissingChild(#[from] NodeMissingChild),
+	#[error(transparent)]
+	#[diagnostic(transparent)]
+	ParseValue(#[from] crate::reader::FailedToParseValue),
+	#[error(transparent)]
+	#[diagnostic(transparent)]
+	IntegerOutOfRange(#[from] IntegerOutOfRange),
+	#[error(transparent)]
+	#[diagnostic(transparent)]
+	Unconsumed(#[from] Unconsumed),
+	#[error(transparent)]
+	#[diagnostic(transparent)]
+	ArgsLength(#[from] ArgsLength),
 }
 impl From<RequiredValue<ValueTypeMismatch>> for QueryError {
 	fn from(value: RequiredValue<ValueTypeMismatch>) -> Self {
@@ -22,71 +40,81 @@ impl From<RequiredValue<ValueTypeMismatch>> for QueryError {
 #[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
 #[diagnostic(code(kdlize::missing_entry))]
 pub struct MissingEntry {
-	#[source_code]
-	pub(crate) src: String,
-	pub(crate) span: miette::LabeledSpan,
-	pub(crate) key: kdl::NodeKey,
+	pub(crate) path: NodePath,
+	#[label("{label}")]
+	pub(crate) span: miette::SourceSpan,
+	pub(crate) label: String,
+	pub(crate) key: Box<kdl::NodeKey>,
 }
 impl MissingEntry {
-	pub(crate) fn new_index(node: kdl::KdlNode, idx: usize) -> Self {
-		let src = node.to_string();
-		let label = format!("missing value at index {idx}");
-		let span = miette::LabeledSpan::new_primary_with_span(Some(label), (0, src.len()));
+	pub(crate) fn new_index(span: miette::SourceSpan, path: &NodePath, idx: usize) -> Self {
 		Self {
-			src,
+			path: path.clone(),
 			span,
-			key: kdl::NodeKey::Index(idx),
+			label: format!("missing value at index {idx}"),
+			key: Box::new(kdl::NodeKey::Index(idx)),
 		}
 	}
-	pub(crate) fn new_prop(node: kdl::KdlNode, key: impl AsRef<str>) -> Self {
-		let src = node.to_string();
-		let label = format!("missing value at property {:?}", key.as_ref());
-		let span = miette::LabeledSpan::new_primary_with_span(Some(label), (0, src.len()));
+	pub(crate) fn new_prop(span: miette::SourceSpan, path: &NodePath, key: impl AsRef<str>) -> Self {
 		Self {
-			src,
+			path: path.clone(),
 			span,
-			key: kdl::NodeKey::Key(kdl::KdlIdentifier::from(key.as_ref())),
+			label: format!("missing value at property {:?}", key.as_ref()),
+			key: Box::new(kdl::NodeKey::Key(kdl::KdlIdentifier::from(key.as_ref()))),
 		}
 	}
 }
 impl std::fmt::Display for MissingEntry {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		match &self.key {
-			kdl::NodeKey::Index(v) => write!(f, "Node {:?} is missing an entry at index {v}", self.src),
+		match self.key.as_ref() {
+			kdl::NodeKey::Index(v) => write!(f, "Node `{}` is missing an entry at index {v}", self.path),
 			kdl::NodeKey::Key(v) => {
-				write!(f, "Node {:?} is missing an entry at property {}", self.src, v.value())
+				write!(f, "Node `{}` is missing an entry at property {}", self.path, v.value())
 			}
 		}
 	}
 }
 
 #[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
-#[error("Entry {value:?} is missing a type identifier")]
 #[diagnostic(code(kdlize::entry_missing_type))]
 pub struct MissingEntryType {
-	#[source_code]
-	pub(crate) src: String,
 	#[label("missing type annotation")]
 	pub(crate) span: miette::SourceSpan,
-	pub(crate) value: kdl::KdlEntry,
+	pub(crate) value: Box<kdl::KdlEntry>,
+	pub(crate) path: Option<NodePath>,
+}
+impl MissingEntryType {
+	/// Attaches the location of the entry, see [`crate::reader::Node::entry_path`].
+	pub fn with_path(mut self, path: NodePath) -> Self {
+		self.path = Some(path);
+		self
+	}
+}
+impl std::fmt::Display for MissingEntryType {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Entry {:?} is missing a type identifier", self.value)?;
+		match &self.path {
+			Some(path) => write!(f, " at {path}"),
+			None => Ok(()),
+		}
+	}
 }
 
 #[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
-#[error("Node {src:?} is missing a child node with name \"{child_name}\"")]
+#[error("Node `{path}` is missing a child node with name \"{}\"", child_name.value())]
 #[diagnostic(code(kdlize::node_missing_child))]
 pub struct NodeMissingChild {
-	#[source_code]
-	pub src: String,
-	pub span: miette::LabeledSpan,
+	pub path: NodePath,
+	#[label("missing child named {:?}", child_name.value())]
+	pub span: miette::SourceSpan,
 	pub child_name: kdl::KdlIdentifier,
 }
 
 #[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
-#[error("Node is missing children document")]
+#[error("Node `{path}` is missing children document")]
 #[diagnostic(code(kdlize::missing_node_document))]
 pub struct MissingNodeDocument {
-	#[source_code]
-	pub(crate) src: String,
+	pub(crate) path: NodePath,
 	#[label("missing children")]
 	pub(crate) node_span: miette::SourceSpan,
 }
@@ -115,7 +143,7 @@ pub enum RequiredChild<E> {
 	Parse(#[from] E),
 }
 
-#[derive(thiserror::Error, Debug, PartialEq, miette::Diagnostic)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
 #[error("Expected '{value}' to be a '{expected_type}', but it is a '{actual_type}'.")]
 #[diagnostic(code(kdlize::value_unexpected_type))]
 pub struct ValueTypeMismatch {
@@ -139,3 +167,427 @@ impl ValueTypeMismatch {
 		}
 	}
 }
+
+/// The integer value does not fit in the type it is being parsed as.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
+#[error("{value} is out of range for {type_name}")]
+#[diagnostic(
+	code(kdlize::integer_out_of_range),
+	help("{type_name} must be between {min} and {max}")
+)]
+pub struct IntegerOutOfRange {
+	pub type_name: &'static str,
+	pub value: i128,
+	pub min: i128,
+	pub max: u128,
+}
+impl IntegerOutOfRange {
+	pub(crate) fn new<T: num_bounds::Bounded>(value: i128) -> Self {
+		Self {
+			type_name: std::any::type_name::<T>(),
+			value,
+			min: T::MIN,
+			max: T::MAX,
+		}
+	}
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
+pub enum InvalidInteger {
+	#[error(transparent)]
+	#[diagnostic(transparent)]
+	TypeMismatch(#[from] ValueTypeMismatch),
+	#[error(transparent)]
+	#[diagnostic(transparent)]
+	OutOfRange(#[from] IntegerOutOfRange),
+}
+impl From<InvalidInteger> for QueryError {
+	fn from(value: InvalidInteger) -> Self {
+		match value {
+			InvalidInteger::TypeMismatch(err) => Self::ValueTypeMismatch(err),
+			InvalidInteger::OutOfRange(err) => Self::IntegerOutOfRange(err),
+		}
+	}
+}
+
+mod num_bounds {
+	/// The bounds of each integer type, widened so they can be reported together.
+	pub trait Bounded {
+		const MIN: i128;
+		const MAX: u128;
+	}
+	macro_rules! impl_bounded {
+		($($target:ty),*) => {
+			$(impl Bounded for $target {
+				const MIN: i128 = <$target>::MIN as i128;
+				const MAX: u128 = <$target>::MAX as u128;
+			})*
+		};
+	}
+	impl_bounded!(u8, i8, u16, i16, u32, i32, u64, i64, u128, usize, isize);
+}
+
+/// The string value is not the name of any variant of the enum it is being parsed as.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
+#[error("'{value}' is not a valid {type_name}")]
+#[diagnostic(code(kdlize::unknown_variant), help("expected one of: {}", expected.join(", ")))]
+pub struct UnknownVariant {
+	pub type_name: &'static str,
+	pub value: String,
+	pub expected: &'static [&'static str],
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
+pub enum InvalidVariant {
+	#[error(transparent)]
+	#[diagnostic(transparent)]
+	TypeMismatch(#[from] ValueTypeMismatch),
+	#[error(transparent)]
+	#[diagnostic(transparent)]
+	Unknown(#[from] UnknownVariant),
+}
+
+/// The entry is annotated with a type other than the one its value is parsed as, e.g. `(time)` for a date.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
+#[error("Expected a value annotated ({expected}), but it is annotated ({found})")]
+#[diagnostic(code(kdlize::type_annotation_mismatch))]
+pub struct TypeAnnotationMismatch {
+	pub expected: &'static str,
+	pub found: String,
+}
+
+/// The string value is not in the format of the type it is being parsed as.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
+#[error("'{value}' is not a valid {type_name}: {reason}")]
+#[diagnostic(code(kdlize::malformed_value), help("expected {format}"))]
+pub struct MalformedValue {
+	pub type_name: &'static str,
+	pub value: String,
+	/// A description of the expected format, e.g. "an RFC 3339 date-time".
+	pub format: &'static str,
+	pub reason: String,
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
+pub enum InvalidString {
+	#[error(transparent)]
+	#[diagnostic(transparent)]
+	TypeMismatch(#[from] ValueTypeMismatch),
+	#[error(transparent)]
+	#[diagnostic(transparent)]
+	Malformed(#[from] MalformedValue),
+	#[error(transparent)]
+	#[diagnostic(transparent)]
+	TypeAnnotation(#[from] TypeAnnotationMismatch),
+}
+
+/// The duration is written with a unit (as a suffix or type annotation) which is not a known unit of time.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
+#[error("'{unit}' is not a unit of time")]
+#[diagnostic(code(kdlize::unknown_duration_unit), help("expected one of: {}", expected.join(", ")))]
+pub struct UnknownDurationUnit {
+	pub unit: String,
+	pub expected: &'static [&'static str],
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
+pub enum InvalidDuration {
+	#[error(transparent)]
+	#[diagnostic(transparent)]
+	TypeMismatch(#[from] ValueTypeMismatch),
+	#[error(transparent)]
+	#[diagnostic(transparent)]
+	Malformed(#[from] MalformedValue),
+	#[error(transparent)]
+	#[diagnostic(transparent)]
+	UnknownUnit(#[from] UnknownDurationUnit),
+}
+
+/// The node has a different number of positional arguments left to read than the collection
+/// it is read as (e.g. an array or tuple) holds, see [`Node::args`](crate::reader::Node::args).
+#[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
+#[error("Node `{path}` has {found} positional arguments left to read, but expected {expected}")]
+#[diagnostic(code(kdlize::args_length))]
+pub struct ArgsLength {
+	pub(crate) path: NodePath,
+	#[label("expected {expected} arguments")]
+	pub(crate) span: miette::SourceSpan,
+	pub expected: usize,
+	pub found: usize,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, miette::Diagnostic)]
+pub enum InvalidArgs {
+	#[error(transparent)]
+	#[diagnostic(transparent)]
+	Length(#[from] ArgsLength),
+	#[error(transparent)]
+	#[diagnostic(transparent)]
+	Parse(#[from] crate::reader::FailedToParseValue),
+}
+impl From<InvalidArgs> for QueryError {
+	fn from(value: InvalidArgs) -> Self {
+		match value {
+			InvalidArgs::Length(err) => Self::ArgsLength(err),
+			InvalidArgs::Parse(err) => Self::ParseValue(err),
+		}
+	}
+}
+
+/// A node is tagged with an id (by its name or type annotation) which is not registered in the
+/// [`Registry`](crate::registry::Registry) it is read with.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
+#[error("Node `{path}` has unknown id {id:?}")]
+#[diagnostic(code(kdlize::unknown_node_id), help("expected one of: {}", expected.join(", ")))]
+pub struct UnknownNodeId {
+	pub path: NodePath,
+	#[label("{label}")]
+	pub span: miette::SourceSpan,
+	label: String,
+	pub id: String,
+	pub expected: Vec<String>,
+}
+impl UnknownNodeId {
+	pub(crate) fn new(path: &NodePath, span: miette::SourceSpan, id: &str, expected: Vec<String>) -> Self {
+		let label = match did_you_mean(id, &expected) {
+			Some(name) => format!("did you mean {name:?}?"),
+			None => "unknown id".to_owned(),
+		};
+		Self {
+			path: path.clone(),
+			span,
+			label,
+			id: id.to_owned(),
+			expected,
+		}
+	}
+}
+
+/// A node has positional arguments, properties or children which were never read.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
+#[error("Node `{path}` has unused entries or children")]
+#[diagnostic(code(kdlize::unconsumed))]
+pub struct Unconsumed {
+	pub path: NodePath,
+	#[label("in this node")]
+	pub span: miette::SourceSpan,
+	#[related]
+	pub unused: Vec<Unused>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnusedKind {
+	Argument(usize),
+	Property(String),
+	Child(String),
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
+#[diagnostic(code(kdlize::unused))]
+pub struct Unused {
+	pub kind: UnusedKind,
+	#[label("never read")]
+	pub span: miette::SourceSpan,
+	#[help]
+	pub suggestion: Option<String>,
+}
+impl Unused {
+	pub(crate) fn new(kind: UnusedKind, span: miette::SourceSpan, accessed: &[String]) -> Self {
+		let suggestion = match &kind {
+			UnusedKind::Argument(_) => None,
+			UnusedKind::Property(name) | UnusedKind::Child(name) => did_you_mean(name, accessed),
+		};
+		Self {
+			kind,
+			span,
+			suggestion: suggestion.map(|name| format!("did you mean {name:?}?")),
+		}
+	}
+}
+impl std::fmt::Display for Unused {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match &self.kind {
+			UnusedKind::Argument(idx) => write!(f, "Unused argument at index {idx}"),
+			UnusedKind::Property(name) => write!(f, "Unused property {name:?}"),
+			UnusedKind::Child(name) => write!(f, "Unused child {name:?}"),
+		}
+	}
+}
+
+/// Finds the closest of the candidate names, if it is within a few edits of the name.
+fn did_you_mean<'a>(name: &str, candidates: &'a [String]) -> Option<&'a str> {
+	let max_distance = (name.chars().count() / 3).max(1);
+	let closest = candidates
+		.iter()
+		.map(|candidate| (edit_distance(name, candidate), candidate));
+	let (distance, candidate) = closest.min_by_key(|(distance, _)| *distance)?;
+	(distance <= max_distance).then_some(candidate.as_str())
+}
+
+/// The edit distance between two strings, where swapping two adjacent characters counts as one edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+	let (a, b) = (a.chars().collect::<Vec<_>>(), b.chars().collect::<Vec<_>>());
+	let mut dist = vec![vec![0; b.len() + 1]; a.len() + 1];
+	for (i, row) in dist.iter_mut().enumerate() {
+		row[0] = i;
+	}
+	for (j, cell) in dist[0].iter_mut().enumerate() {
+		*cell = j;
+	}
+	for i in 1..=a.len() {
+		for j in 1..=b.len() {
+			let cost = usize::from(a[i - 1] != b[j - 1]);
+			let mut best = (dist[i - 1][j] + 1)
+				.min(dist[i][j - 1] + 1)
+				.min(dist[i - 1][j - 1] + cost);
+			if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+				best = best.min(dist[i - 2][j - 2] + 1);
+			}
+			dist[i][j] = best;
+		}
+	}
+	dist[a.len()][b.len()]
+}
+
+/// Returned by a reader which recorded its errors to a [`crate::reader::Diagnostics`] sink,
+/// to stop reading the node that failed. It is not recorded itself.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
+#[error("errors were recorded to the diagnostics sink")]
+#[diagnostic(code(kdlize::reported))]
+pub struct Reported;
+
+/// All of the errors recorded while reading a document.
+#[derive(thiserror::Error, Debug)]
+#[error("{} errors while reading", errors.len())]
+pub struct MultipleErrors {
+	pub errors: Vec<miette::Report>,
+}
+impl miette::Diagnostic for MultipleErrors {
+	fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+		Some(Box::new("kdlize::multiple_errors"))
+	}
+
+	fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn miette::Diagnostic> + 'a>> {
+		Some(Box::new(
+			self.errors.iter().map(|err| err.as_ref() as &dyn miette::Diagnostic),
+		))
+	}
+}
+
+/// An error from loading a value from KDL text via [`crate::from_str`] or [`crate::from_file`].
+/// It carries the text (and file name) so that its labels are rendered with their source.
+#[derive(Debug)]
+pub struct LoadError {
+	kind: LoadErrorKind,
+	src: LoadSource,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum LoadErrorKind {
+	#[error("Failed to read {}", path.display())]
+	Io {
+		path: std::path::PathBuf,
+		#[source]
+		error: std::io::Error,
+	},
+	/// The text is not a valid KDL document.
+	#[error(transparent)]
+	Parse(#[from] kdl::KdlError),
+	/// The document could not be read as the requested type.
+	#[error("{0}")]
+	Read(miette::Report),
+}
+impl From<miette::Report> for LoadErrorKind {
+	fn from(value: miette::Report) -> Self {
+		Self::Read(value)
+	}
+}
+
+#[derive(Debug)]
+pub(crate) enum LoadSource {
+	None,
+	Text(String),
+	Named(miette::NamedSource<String>),
+}
+
+impl LoadError {
+	pub(crate) fn new(kind: LoadErrorKind, src: LoadSource) -> Self {
+		Self { kind, src }
+	}
+
+	pub(crate) fn with_name(mut self, name: String) -> Self {
+		if let LoadSource::Text(text) = self.src {
+			self.src = LoadSource::Named(miette::NamedSource::new(name, text));
+		}
+		self
+	}
+
+	pub fn kind(&self) -> &LoadErrorKind {
+		&self.kind
+	}
+
+	pub fn into_kind(self) -> LoadErrorKind {
+		self.kind
+	}
+
+	fn diagnostic(&self) -> Option<&dyn miette::Diagnostic> {
+		match &self.kind {
+			LoadErrorKind::Io { .. } => None,
+			LoadErrorKind::Parse(err) => Some(err),
+			LoadErrorKind::Read(err) => Some(err.as_ref()),
+		}
+	}
+}
+
+impl std::fmt::Display for LoadError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		self.kind.fmt(f)
+	}
+}
+
+impl std::error::Error for LoadError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		self.kind.source()
+	}
+}
+
+impl miette::Diagnostic for LoadError {
+	fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+		match &self.kind {
+			LoadErrorKind::Io { .. } => Some(Box::new("kdlize::io")),
+			_ => self.diagnostic()?.code(),
+		}
+	}
+
+	fn severity(&self) -> Option<miette::Severity> {
+		self.diagnostic()?.severity()
+	}
+
+	fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+		self.diagnostic()?.help()
+	}
+
+	fn url<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+		self.diagnostic()?.url()
+	}
+
+	fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+		match &self.src {
+			LoadSource::None => None,
+			LoadSource::Text(text) => Some(text),
+			LoadSource::Named(src) => Some(src),
+		}
+	}
+
+	fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+		self.diagnostic()?.labels()
+	}
+
+	fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn miette::Diagnostic> + 'a>> {
+		self.diagnostic()?.related()
+	}
+
+	fn diagnostic_source(&self) -> Option<&dyn miette::Diagnostic> {
+		self.diagnostic()?.diagnostic_source()
+	}
+}
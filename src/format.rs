@@ -0,0 +1,372 @@
+//! Formatting of documents for output, based on the structure of the document rather than its text.
+
+/// The whitespace used for each level of indentation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+	Tabs,
+	Spaces(usize),
+}
+
+impl Indent {
+	fn as_str(&self) -> String {
+		match self {
+			Self::Tabs => "\t".to_owned(),
+			Self::Spaces(count) => " ".repeat(*count),
+		}
+	}
+}
+
+/// The order properties are written in, relative to each other and to positional arguments.
+/// The order of positional arguments is never changed, since it is meaningful.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PropOrder {
+	/// Entries are written in the order they were added.
+	#[default]
+	Preserve,
+	/// Positional arguments are written first, followed by properties in the order they were added.
+	ArgsFirst,
+	/// Positional arguments are written first, followed by properties sorted by name.
+	Sorted,
+}
+
+/// Options for writing a document via [`Format::apply`] or [`Format::to_string`].
+#[derive(Debug, Clone)]
+pub struct Format {
+	pub indent: Indent,
+	/// Nodes whose entries would make a line wider than this are continued on the next line(s)
+	/// with a `\` line escape. Tabs are counted as 4 columns.
+	pub max_width: Option<usize>,
+	pub prop_order: PropOrder,
+	/// Whether strings containing line breaks are written as `"""` multi-line strings.
+	pub multi_line_strings: bool,
+	/// Whether strings containing quotes or backslashes are written as `#"raw"#` strings rather than escaped.
+	pub raw_strings: bool,
+	/// Whether comments are removed.
+	pub no_comments: bool,
+}
+
+impl Default for Format {
+	fn default() -> Self {
+		Self {
+			indent: Indent::Spaces(4),
+			max_width: None,
+			prop_order: PropOrder::Preserve,
+			multi_line_strings: false,
+			raw_strings: false,
+			no_comments: false,
+		}
+	}
+}
+
+const TAB_WIDTH: usize = 4;
+
+impl Format {
+	/// Formats a document in place, discarding any existing formatting (but not comments, unless `no_comments`).
+	pub fn apply(&self, doc: &mut kdl::KdlDocument) {
+		let indent = self.indent.as_str();
+		let config = kdl::FormatConfig::builder()
+			.indent(&indent)
+			.no_comments(self.no_comments)
+			.build();
+		doc.autoformat_config(&config);
+		self.apply_nodes(doc, &indent, 0);
+	}
+
+	/// Writes a document with this format, leaving the original document unchanged.
+	pub fn to_string(&self, doc: &kdl::KdlDocument) -> String {
+		let mut doc = doc.clone();
+		self.apply(&mut doc);
+		doc.to_string()
+	}
+
+	fn apply_nodes(&self, doc: &mut kdl::KdlDocument, indent: &str, depth: usize) {
+		for node in doc.nodes_mut() {
+			let node_indent = indent.repeat(depth);
+			self.order_props(node);
+			for entry in node.entries_mut() {
+				if let Some(repr) = self.string_repr(entry.value(), &format!("{node_indent}{indent}")) {
+					set_value_repr(entry, repr);
+				}
+			}
+			if let Some(max_width) = self.max_width {
+				wrap_entries(node, &node_indent, indent, max_width);
+			}
+			if let Some(children) = node.children_mut() {
+				self.apply_nodes(children, indent, depth + 1);
+			}
+		}
+	}
+
+	fn order_props(&self, node: &mut kdl::KdlNode) {
+		let entries = node.entries_mut();
+		match self.prop_order {
+			PropOrder::Preserve => {}
+			// Stable sorts, so positional arguments keep their order.
+			PropOrder::ArgsFirst => entries.sort_by_key(|entry| entry.name().is_some()),
+			PropOrder::Sorted => entries.sort_by(|a, b| {
+				let key = |entry: &kdl::KdlEntry| entry.name().map(|name| name.value().to_owned());
+				key(a).cmp(&key(b))
+			}),
+		}
+	}
+
+	/// The text to write for a string value, if it is written as something other than `kdl`'s escaped string.
+	/// `indent` is the indentation of the lines of a multi-line string.
+	fn string_repr(&self, value: &kdl::KdlValue, indent: &str) -> Option<String> {
+		let kdl::KdlValue::String(value) = value else {
+			return None;
+		};
+		// Raw strings cannot escape anything, so values which need escapes are written as escaped strings.
+		let raw = self.raw_strings && value.contains(['"', '\\']) && !value.chars().any(needs_escape);
+		if self.multi_line_strings && value.contains('\n') {
+			let (hashes, value) = match raw && !value.split('\n').any(is_blank_line) {
+				true => (raw_hashes(value), value.clone()),
+				false => (String::new(), escape_multi_line(value)),
+			};
+			let lines = value.split('\n').map(|line| match line.is_empty() {
+				true => String::new(),
+				false => format!("{indent}{line}"),
+			});
+			let lines = lines.collect::<Vec<_>>().join("\n");
+			return Some(format!("{hashes}\"\"\"\n{lines}\n{indent}\"\"\"{hashes}"));
+		}
+		if raw && !value.contains('\n') {
+			let hashes = raw_hashes(value);
+			return Some(format!("{hashes}\"{value}\"{hashes}"));
+		}
+		// The value is otherwise written by `kdl`, which does not escape these.
+		if value.chars().any(needs_escape) {
+			return Some(escape_single_line(value));
+		}
+		None
+	}
+}
+
+fn set_value_repr(entry: &mut kdl::KdlEntry, value_repr: String) {
+	// Nodes only add a space before entries without a format, so a new format needs its own.
+	let mut format = entry.format().cloned().unwrap_or_else(|| kdl::KdlEntryFormat {
+		leading: " ".to_owned(),
+		..Default::default()
+	});
+	format.value_repr = value_repr;
+	entry.set_format(format);
+}
+
+/// The `#`s needed around a raw string so that its content cannot end it early.
+fn raw_hashes(value: &str) -> String {
+	let mut count = 1;
+	while value.contains(&format!("\"{}", "#".repeat(count))) {
+		count += 1;
+	}
+	"#".repeat(count)
+}
+
+/// Whether a character cannot be written as itself in a string, other than as the `\n` between lines.
+/// These are the code points KDL disallows in documents, and newlines other than `\n`.
+fn needs_escape(ch: char) -> bool {
+	matches!(
+		ch,
+		'\u{0}'..='\u{8}'
+			| '\u{b}'..='\u{1f}'
+			| '\u{7f}'
+			| '\u{85}'
+			| '\u{200e}'..='\u{200f}'
+			| '\u{2028}'..='\u{202e}'
+			| '\u{2066}'..='\u{2069}'
+			| '\u{feff}'
+	)
+}
+
+/// Writes a value as an escaped single-line string.
+fn escape_single_line(value: &str) -> String {
+	let mut escaped = String::with_capacity(value.len() + 2);
+	escaped.push('"');
+	for ch in value.chars() {
+		match ch {
+			'\\' => escaped.push_str("\\\\"),
+			'"' => escaped.push_str("\\\""),
+			'\n' => escaped.push_str("\\n"),
+			'\r' => escaped.push_str("\\r"),
+			'\t' => escaped.push_str("\\t"),
+			ch if needs_escape(ch) => escaped.push_str(&format!("\\u{{{:x}}}", ch as u32)),
+			ch => escaped.push(ch),
+		}
+	}
+	escaped.push('"');
+	escaped
+}
+
+/// Whether a line of a multi-line string is only whitespace, which KDL reads as an empty line.
+fn is_blank_line(line: &str) -> bool {
+	!line.is_empty() && line.chars().all(char::is_whitespace)
+}
+
+/// Escapes the text of a multi-line string, which may contain quotes but not `"""`.
+pub(crate) fn escape_multi_line(value: &str) -> String {
+	let mut escaped = String::with_capacity(value.len());
+	for (idx, line) in value.split('\n').enumerate() {
+		if idx > 0 {
+			escaped.push('\n');
+		}
+		let blank = is_blank_line(line);
+		for ch in line.chars() {
+			match ch {
+				'\\' => escaped.push_str("\\\\"),
+				'\r' => escaped.push_str("\\r"),
+				'"' if escaped.ends_with("\"\"") => escaped.push_str("\\\""),
+				' ' if blank => escaped.push_str("\\s"),
+				'\t' if blank => escaped.push_str("\\t"),
+				ch if blank || needs_escape(ch) => escaped.push_str(&format!("\\u{{{:x}}}", ch as u32)),
+				ch => escaped.push(ch),
+			}
+		}
+	}
+	escaped
+}
+
+fn width(text: &str) -> usize {
+	text.chars().map(|ch| if ch == '\t' { TAB_WIDTH } else { 1 }).sum()
+}
+
+/// Moves entries onto continuation lines where they would make the node's line wider than `max_width`.
+fn wrap_entries(node: &mut kdl::KdlNode, node_indent: &str, indent: &str, max_width: usize) {
+	let continuation = format!("{node_indent}{indent}");
+	let mut column = width(node_indent) + width(&node.name().to_string());
+	let line_start = width(&continuation);
+	for entry in node.entries_mut() {
+		let text = entry_text(entry);
+		let first_line = text.split('\n').next().unwrap_or_default();
+		// An entry is never moved if it would not fit on its own line either.
+		if column + 1 + width(first_line) > max_width && column > line_start {
+			let mut format = entry.format().cloned().unwrap_or_else(|| kdl::KdlEntryFormat {
+				value_repr: entry.value().to_string(),
+				..Default::default()
+			});
+			format.leading = format!(" \\\n{continuation}");
+			entry.set_format(format);
+			column = line_start;
+		} else {
+			column += 1;
+		}
+		column = match text.rsplit_once('\n') {
+			Some((_, last_line)) => width(last_line),
+			None => column + width(&text),
+		};
+	}
+}
+
+/// The text of an entry, without any leading whitespace.
+fn entry_text(entry: &kdl::KdlEntry) -> String {
+	let mut entry = entry.clone();
+	if let Some(format) = entry.format_mut() {
+		format.leading.clear();
+	}
+	entry.to_string()
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn format(src: &str, format: Format) -> String {
+		let doc = src.parse::<kdl::KdlDocument>().expect("valid kdl");
+		let out = format.to_string(&doc);
+		// Formatting never changes the data in the document.
+		let mut reparsed = out.parse::<kdl::KdlDocument>().expect("formatted kdl is valid");
+		let mut original = doc;
+		reparsed.clear_format_recursive();
+		original.clear_format_recursive();
+		if format.prop_order == PropOrder::Preserve {
+			assert_eq!(reparsed.to_string(), original.to_string());
+		}
+		out
+	}
+
+	#[test]
+	fn indent() {
+		let src = "a {\n  b {\n     c 1\n  }\n}";
+		assert_eq!(format(src, Format::default()), "a {\n    b {\n        c 1\n    }\n}\n");
+		let tabs = Format {
+			indent: Indent::Tabs,
+			..Default::default()
+		};
+		assert_eq!(format(src, tabs), "a {\n\tb {\n\t\tc 1\n\t}\n}\n");
+	}
+
+	#[test]
+	fn strings_are_not_corrupted() {
+		// Escapes and runs of spaces inside strings must survive formatting.
+		let src = r#"node "a\nb    c" "tab\there""#;
+		let tabs = Format {
+			indent: Indent::Tabs,
+			..Default::default()
+		};
+		assert_eq!(format(src, tabs), "node \"a\\nb    c\" \"tab\\there\"\n");
+	}
+
+	#[test]
+	fn prop_order() {
+		let src = "node b=2 1 a=1 2";
+		let args_first = Format {
+			prop_order: PropOrder::ArgsFirst,
+			..Default::default()
+		};
+		assert_eq!(format(src, args_first), "node 1 2 b=2 a=1\n");
+		let sorted = Format {
+			prop_order: PropOrder::Sorted,
+			..Default::default()
+		};
+		assert_eq!(format(src, sorted), "node 1 2 a=1 b=2\n");
+	}
+
+	#[test]
+	fn multi_line_strings() {
+		let src = "parent {\n    node \"line one\\nline \\\"two\\\"\\n\\nline four\\\\\"\n}";
+		let multi_line = Format {
+			multi_line_strings: true,
+			..Default::default()
+		};
+		let expected =
+			"parent {\n    node \"\"\"\n        line one\n        line \"two\"\n\n        line four\\\\\n        \"\"\"\n}\n";
+		assert_eq!(format(src, multi_line), expected);
+	}
+
+	#[test]
+	fn multi_line_escapes() {
+		// Whitespace-only lines and disallowed code points are escaped, even when raw strings are preferred.
+		let src = "node \"a\\n  \\n\\tb\" \"bell\\u{7}\\\\\\nc\"";
+		let multi_line = Format {
+			multi_line_strings: true,
+			raw_strings: true,
+			..Default::default()
+		};
+		let expected =
+			"node \"\"\"\n    a\n    \\s\\s\n    \tb\n    \"\"\" \"\"\"\n    bell\\u{7}\\\\\n    c\n    \"\"\"\n";
+		assert_eq!(format(src, multi_line), expected);
+	}
+
+	#[test]
+	fn raw_strings() {
+		let src = r##"node "C:\\path" "say \"hi\"#" plain"##;
+		let raw = Format {
+			raw_strings: true,
+			..Default::default()
+		};
+		assert_eq!(
+			format(src, raw.clone()),
+			"node #\"C:\\path\"# ##\"say \"hi\"#\"## plain\n"
+		);
+		assert_eq!(format("node \"bell\\u{7}\\\\\"", raw), "node \"bell\\u{7}\\\\\"\n");
+	}
+
+	#[test]
+	fn max_width() {
+		let src = "root {\n    node first second third=3 fourth=4\n}";
+		let narrow = Format {
+			max_width: Some(24),
+			..Default::default()
+		};
+		let expected = "root {\n    node first second \\\n        third=3 fourth=4\n}\n";
+		assert_eq!(format(src, narrow), expected);
+	}
+}
@@ -1,9 +1,59 @@
+pub use kdl;
+
+// Implements FromKdlValue and AsKdlValue for a type which is written as a string via FromStr/ToString,
+// where `$format` describes the expected text for errors, and `$annotation` is the type annotation it is written with.
+macro_rules! impl_kdlvalue_parse {
+	($target:ty, $format:expr) => {
+		impl_kdlvalue_parse!($target, $format, None);
+	};
+	($target:ty, $format:expr, $annotation:expr) => {
+		impl<'doc> $crate::FromKdlValue<'doc> for $target {
+			type Error = $crate::error::InvalidString;
+			fn from_kdl(value: &'doc kdl::KdlValue) -> Result<Self, Self::Error> {
+				$crate::parse_str(value, $format, str::parse::<$target>)
+			}
+
+			fn type_annotation() -> Option<&'static str> {
+				$annotation
+			}
+		}
+		impl $crate::AsKdlValue for $target {
+			fn as_kdl(&self) -> kdl::KdlValue {
+				kdl::KdlValue::String(self.to_string())
+			}
+
+			fn type_annotation(&self) -> Option<&'static str> {
+				$annotation
+			}
+		}
+	};
+}
+
+mod args;
+#[cfg(feature = "binary")]
+mod binary;
 pub mod builder;
+#[cfg(any(feature = "chrono", feature = "time"))]
+mod datetime;
+#[cfg(feature = "serde")]
+pub mod de;
+mod duration;
 pub mod error;
+pub mod format;
+mod net;
 pub mod reader;
+pub mod registry;
+#[cfg(feature = "serde")]
+pub mod ser;
+#[cfg(any(feature = "uuid", feature = "url", feature = "semver"))]
+mod strings;
+pub mod version;
 
-use error::ValueTypeMismatch;
+use error::{IntegerOutOfRange, InvalidInteger, ValueTypeMismatch};
 
+#[cfg(feature = "derive")]
+pub use kdlize_derive::NodeId;
+/// The id of a node's type, which tags trait objects read and written via a [`registry::Registry`].
 pub trait NodeId {
 	fn id() -> &'static str
 	where
@@ -27,25 +77,78 @@ macro_rules! impl_kdl_node {
 	};
 }
 
+#[cfg(feature = "derive")]
+pub use kdlize_derive::{AsKdlValue, FromKdlValue};
 pub trait FromKdlValue<'doc> {
 	type Error;
 	fn from_kdl(value: &'doc kdl::KdlValue) -> Result<Self, Self::Error>
 	where
 		Self: Sized;
+
+	/// Parses a value along with the type annotation of its entry, for types whose value depends on it
+	/// (e.g. the unit of `(ms)500`). Used by [`reader::EntryExt::to`].
+	fn from_kdl_typed(value: &'doc kdl::KdlValue, _ty: Option<&'doc str>) -> Result<Self, Self::Error>
+	where
+		Self: Sized,
+	{
+		Self::from_kdl(value)
+	}
+
+	/// The type annotation values of this type may be written with, e.g. `(date)`.
+	/// Entries annotated with any other type fail to parse via [`reader::EntryExt::to`].
+	fn type_annotation() -> Option<&'static str>
+	where
+		Self: Sized,
+	{
+		None
+	}
 }
 
 pub trait AsKdlValue {
 	fn as_kdl(&self) -> kdl::KdlValue;
+
+	/// The type annotation this value is written with by the builder, e.g. `(date)`.
+	fn type_annotation(&self) -> Option<&'static str> {
+		None
+	}
+
+	/// The value written with a type annotation chosen by the builder's `Typed`, for types whose value depends on it
+	/// (e.g. the encoding of `(hex)` bytes).
+	fn as_kdl_typed(&self, _ty: &str) -> kdl::KdlValue {
+		self.as_kdl()
+	}
 }
-impl<V: AsKdlValue> AsKdlValue for &V {
+impl<V: AsKdlValue + ?Sized> AsKdlValue for &V {
 	fn as_kdl(&self) -> kdl::KdlValue {
 		V::as_kdl(self)
 	}
+
+	fn type_annotation(&self) -> Option<&'static str> {
+		V::type_annotation(self)
+	}
+
+	fn as_kdl_typed(&self, ty: &str) -> kdl::KdlValue {
+		V::as_kdl_typed(self, ty)
+	}
 }
 impl<V: AsKdlValue + Clone> AsKdlValue for std::borrow::Cow<'_, V> {
 	fn as_kdl(&self) -> kdl::KdlValue {
 		V::as_kdl(self.as_ref())
 	}
+
+	fn type_annotation(&self) -> Option<&'static str> {
+		V::type_annotation(self.as_ref())
+	}
+
+	fn as_kdl_typed(&self, ty: &str) -> kdl::KdlValue {
+		V::as_kdl_typed(self.as_ref(), ty)
+	}
+}
+
+impl AsKdlValue for kdl::KdlValue {
+	fn as_kdl(&self) -> kdl::KdlValue {
+		self.clone()
+	}
 }
 
 impl<'doc> FromKdlValue<'doc> for &'doc kdl::KdlValue {
@@ -60,7 +163,7 @@ impl<'doc> FromKdlValue<'doc> for &'doc str {
 	fn from_kdl(value: &'doc kdl::KdlValue) -> Result<Self, Self::Error> {
 		match value {
 			kdl::KdlValue::String(value) => Ok(value.as_str()),
-			_ => Err(ValueTypeMismatch::new(&value, "String")),
+			_ => Err(ValueTypeMismatch::new(value, "String")),
 		}
 	}
 }
@@ -75,11 +178,6 @@ impl AsKdlValue for str {
 		kdl::KdlValue::String(self.to_owned())
 	}
 }
-impl AsKdlValue for &str {
-	fn as_kdl(&self) -> kdl::KdlValue {
-		str::as_kdl(self)
-	}
-}
 impl AsKdlValue for String {
 	fn as_kdl(&self) -> kdl::KdlValue {
 		self.as_str().as_kdl()
@@ -103,6 +201,24 @@ impl AsKdlValue for std::path::PathBuf {
 	}
 }
 
+/// Parses a string value via `parse`, where `format` describes the text it expects for errors.
+pub(crate) fn parse_str<'doc, T, E: std::fmt::Display>(
+	value: &'doc kdl::KdlValue,
+	format: &'static str,
+	parse: impl FnOnce(&'doc str) -> Result<T, E>,
+) -> Result<T, error::InvalidString> {
+	let text = <&str>::from_kdl(value)?;
+	parse(text).map_err(|err| {
+		let malformed = error::MalformedValue {
+			type_name: std::any::type_name::<T>(),
+			value: text.to_owned(),
+			format,
+			reason: err.to_string(),
+		};
+		malformed.into()
+	})
+}
+
 #[derive(thiserror::Error, Debug, miette::Diagnostic)]
 pub enum FailedToParseValueString {
 	#[error(transparent)]
@@ -130,7 +246,7 @@ macro_rules! impl_kdlvalue_str {
 				let value = match value {
 					kdl::KdlValue::String(value) => value,
 					_ => {
-						let type_mismatch = $crate::error::ValueTypeMismatch::new(&value, "String");
+						let type_mismatch = $crate::error::ValueTypeMismatch::new(value, "String");
 						return Err($crate::MacroReport::new(type_mismatch));
 					}
 				};
@@ -156,6 +272,7 @@ macro_rules! impl_kdlvalue_str {
 
 #[cfg(test)]
 mod test {
+	#[allow(dead_code)]
 	struct ExampleStr;
 	impl std::str::FromStr for ExampleStr {
 		type Err = std::convert::Infallible;
@@ -192,7 +309,7 @@ impl<'doc> FromKdlValue<'doc> for i128 {
 	fn from_kdl(value: &'doc kdl::KdlValue) -> Result<Self, Self::Error> {
 		match value {
 			kdl::KdlValue::Integer(value) => Ok(*value),
-			_ => Err(ValueTypeMismatch::new(&value, "Integer")),
+			_ => Err(ValueTypeMismatch::new(value, "Integer")),
 		}
 	}
 }
@@ -201,25 +318,140 @@ impl AsKdlValue for i128 {
 		kdl::KdlValue::Integer(*self)
 	}
 }
-impl_kdlvalue_primitive!(u8, i128);
-impl_kdlvalue_primitive!(i8, i128);
-impl_kdlvalue_primitive!(u16, i128);
-impl_kdlvalue_primitive!(i16, i128);
-impl_kdlvalue_primitive!(u32, i128);
-impl_kdlvalue_primitive!(i32, i128);
-impl_kdlvalue_primitive!(u64, i128);
-impl_kdlvalue_primitive!(i64, i128);
-impl_kdlvalue_primitive!(u128, i128);
-//impl_kdlvalue_primitive!(i128,  i128);
-impl_kdlvalue_primitive!(usize, i128);
-impl_kdlvalue_primitive!(isize, i128);
+macro_rules! impl_kdlvalue_integer {
+	($target:ty) => {
+		impl<'doc> FromKdlValue<'doc> for $target {
+			type Error = InvalidInteger;
+			fn from_kdl(value: &'doc kdl::KdlValue) -> Result<Self, Self::Error> {
+				let value = i128::from_kdl(value)?;
+				<$target>::try_from(value).map_err(|_| IntegerOutOfRange::new::<$target>(value).into())
+			}
+		}
+		impl_kdlvalue_integer!(@as_kdl $target);
+	};
+	(@as_kdl $(#[$attr:meta])* $target:ty) => {
+		$(#[$attr])*
+		impl AsKdlValue for $target {
+			fn as_kdl(&self) -> kdl::KdlValue {
+				match i128::try_from(*self) {
+					Ok(value) => kdl::KdlValue::Integer(value),
+					// KDL integers are limited to i128, so larger values are written as a string of digits
+					Err(_) => kdl::KdlValue::String(self.to_string()),
+				}
+			}
+		}
+		impl<'doc> FromKdlValue<'doc> for std::num::Wrapping<$target> {
+			type Error = ValueTypeMismatch;
+			fn from_kdl(value: &'doc kdl::KdlValue) -> Result<Self, Self::Error> {
+				match <$target>::from_kdl(value) {
+					Ok(value) => Ok(Self(value)),
+					Err(InvalidInteger::OutOfRange(err)) => Ok(Self(err.value as $target)),
+					Err(InvalidInteger::TypeMismatch(err)) => Err(err),
+				}
+			}
+		}
+		impl AsKdlValue for std::num::Wrapping<$target> {
+			fn as_kdl(&self) -> kdl::KdlValue {
+				self.0.as_kdl()
+			}
+		}
+		impl<'doc> FromKdlValue<'doc> for std::num::Saturating<$target> {
+			type Error = ValueTypeMismatch;
+			fn from_kdl(value: &'doc kdl::KdlValue) -> Result<Self, Self::Error> {
+				match <$target>::from_kdl(value) {
+					Ok(value) => Ok(Self(value)),
+					Err(InvalidInteger::OutOfRange(err)) if err.value < 0 => Ok(Self(<$target>::MIN)),
+					Err(InvalidInteger::OutOfRange(_)) => Ok(Self(<$target>::MAX)),
+					Err(InvalidInteger::TypeMismatch(err)) => Err(err),
+				}
+			}
+		}
+		impl AsKdlValue for std::num::Saturating<$target> {
+			fn as_kdl(&self) -> kdl::KdlValue {
+				self.0.as_kdl()
+			}
+		}
+	};
+}
+
+impl_kdlvalue_integer!(u8);
+impl_kdlvalue_integer!(i8);
+impl_kdlvalue_integer!(u16);
+impl_kdlvalue_integer!(i16);
+impl_kdlvalue_integer!(u32);
+impl_kdlvalue_integer!(i32);
+impl_kdlvalue_integer!(u64);
+impl_kdlvalue_integer!(i64);
+impl_kdlvalue_integer!(usize);
+impl_kdlvalue_integer!(isize);
+
+impl<'doc> FromKdlValue<'doc> for u128 {
+	type Error = InvalidInteger;
+	fn from_kdl(value: &'doc kdl::KdlValue) -> Result<Self, Self::Error> {
+		// Values above i128::MAX are written as a string of digits
+		if let kdl::KdlValue::String(digits) = value {
+			if let Ok(value @ 0x8000_0000_0000_0000_0000_0000_0000_0000..) = digits.parse::<u128>() {
+				return Ok(value);
+			}
+		}
+		let value = i128::from_kdl(value)?;
+		u128::try_from(value).map_err(|_| IntegerOutOfRange::new::<u128>(value).into())
+	}
+}
+impl_kdlvalue_integer!(@as_kdl
+	/// Values above `i128::MAX` cannot be written as a KDL integer, so they are written as a string of their digits
+	/// instead. `u128::from_kdl` reads such strings back, but other readers of the document will see a string.
+	u128
+);
+
+#[cfg(test)]
+mod test_integer {
+	use super::*;
+	use std::num::{Saturating, Wrapping};
+
+	#[test]
+	fn out_of_range() {
+		let value = kdl::KdlValue::Integer(300);
+		assert_eq!(
+			u8::from_kdl(&value),
+			Err(InvalidInteger::OutOfRange(IntegerOutOfRange {
+				type_name: "u8",
+				value: 300,
+				min: 0,
+				max: 255,
+			}))
+		);
+		assert!(u64::from_kdl(&kdl::KdlValue::Integer(-1)).is_err());
+		assert_eq!(i16::from_kdl(&kdl::KdlValue::Integer(-300)), Ok(-300));
+	}
+
+	#[test]
+	fn wrapping_saturating() {
+		let value = kdl::KdlValue::Integer(300);
+		assert_eq!(Wrapping::<u8>::from_kdl(&value), Ok(Wrapping(44)));
+		assert_eq!(Saturating::<u8>::from_kdl(&value), Ok(Saturating(255)));
+		assert_eq!(
+			Saturating::<u8>::from_kdl(&kdl::KdlValue::Integer(-1)),
+			Ok(Saturating(0))
+		);
+	}
+
+	#[test]
+	fn large_u128() {
+		let value = u128::MAX.as_kdl();
+		assert_eq!(value, kdl::KdlValue::String(u128::MAX.to_string()));
+		assert_eq!(u128::from_kdl(&value), Ok(u128::MAX));
+		assert_eq!(5u128.as_kdl(), kdl::KdlValue::Integer(5));
+		assert!(u128::from_kdl(&kdl::KdlValue::String("5".into())).is_err());
+	}
+}
 
 impl<'doc> FromKdlValue<'doc> for f64 {
 	type Error = ValueTypeMismatch;
 	fn from_kdl(value: &'doc kdl::KdlValue) -> Result<Self, Self::Error> {
 		match value {
 			kdl::KdlValue::Float(value) => Ok(*value),
-			_ => Err(ValueTypeMismatch::new(&value, "Float")),
+			_ => Err(ValueTypeMismatch::new(value, "Float")),
 		}
 	}
 }
@@ -236,7 +468,7 @@ impl<'doc> FromKdlValue<'doc> for bool {
 	fn from_kdl(value: &'doc kdl::KdlValue) -> Result<Self, Self::Error> {
 		match value {
 			kdl::KdlValue::Bool(value) => Ok(*value),
-			_ => Err(ValueTypeMismatch::new(&value, "Bool")),
+			_ => Err(ValueTypeMismatch::new(value, "Bool")),
 		}
 	}
 }
@@ -256,18 +488,38 @@ where
 			Some(value) => value.as_kdl(),
 		}
 	}
+
+	fn type_annotation(&self) -> Option<&'static str> {
+		self.as_ref().and_then(V::type_annotation)
+	}
+
+	fn as_kdl_typed(&self, ty: &str) -> kdl::KdlValue {
+		match self {
+			None => kdl::KdlValue::Null,
+			Some(value) => value.as_kdl_typed(ty),
+		}
+	}
+}
+
+/// A collection which is read from all of the remaining positional arguments of a node, via [`reader::Node::args`].
+pub trait FromKdlArgs<'doc>: Sized {
+	fn from_args<Context>(node: &mut reader::Node<'doc, Context>) -> Result<Self, error::InvalidArgs>;
 }
 
+#[cfg(feature = "derive")]
+pub use kdlize_derive::FromKdlNode;
 pub trait FromKdlNode<'doc, Context> {
 	type Error;
 	fn from_kdl(node: &mut reader::Node<'doc, Context>) -> Result<Self, Self::Error>
 	where
 		Self: Sized;
 }
+#[cfg(feature = "derive")]
+pub use kdlize_derive::AsKdlNode;
 pub trait AsKdlNode {
 	fn as_kdl(&self) -> builder::Node;
 }
-impl<V: AsKdlNode> AsKdlNode for &V {
+impl<V: AsKdlNode + ?Sized> AsKdlNode for &V {
 	fn as_kdl(&self) -> builder::Node {
 		(*self).as_kdl()
 	}
@@ -282,15 +534,112 @@ impl<V: AsKdlNode> AsKdlNode for Option<V> {
 }
 
 pub trait DocumentExt {
+	#[deprecated = "use `format::Format`, which formats the document without changing the text of its values"]
 	fn to_string_unescaped(&self) -> String;
 }
+#[allow(deprecated)]
 impl DocumentExt for kdl::KdlDocument {
 	fn to_string_unescaped(&self) -> String {
-		let doc = self.to_string();
-		let doc = doc.replace("\\r", "");
-		let doc = doc.replace("\\n", "\n");
-		let doc = doc.replace("\\t", "\t");
-		let doc = doc.replace("    ", "\t");
-		doc
+		let format = format::Format {
+			indent: format::Indent::Tabs,
+			multi_line_strings: true,
+			..Default::default()
+		};
+		format.to_string(self)
 	}
 }
+
+/// Parses a document from text, and reads it into a value via its `FromKdlNode` impl,
+/// where the root nodes of the document are the value's children (see [`reader::Document`]).
+/// All errors encountered while reading are reported together, with the text attached as their source.
+pub fn from_str<T, Context>(src: &str, ctx: &Context) -> Result<T, error::LoadError>
+where
+	T: for<'doc> FromKdlNode<'doc, Context>,
+	for<'doc> miette::Report: From<<T as FromKdlNode<'doc, Context>>::Error>,
+{
+	from_str_version(src, Some(version::Version::V2), ctx)
+}
+
+/// Parses and reads a document via [`from_str`], where the document is written in a specific version of KDL,
+/// or in either version if `version` is `None` (see [`version::Version::detect`]).
+pub fn from_str_version<T, Context>(
+	src: &str,
+	version: Option<version::Version>,
+	ctx: &Context,
+) -> Result<T, error::LoadError>
+where
+	T: for<'doc> FromKdlNode<'doc, Context>,
+	for<'doc> miette::Report: From<<T as FromKdlNode<'doc, Context>>::Error>,
+{
+	let load = || -> Result<T, error::LoadErrorKind> {
+		let doc = match version {
+			Some(version) => version.parse(src)?,
+			None => version::Version::detect(src)?.1,
+		};
+		let diagnostics = reader::Diagnostics::default();
+		let result = reader::Document::new(&doc, ctx)
+			.with_diagnostics(diagnostics.clone())
+			.to::<T>();
+		Ok(diagnostics.finish(result)?)
+	};
+	load().map_err(|kind| error::LoadError::new(kind, error::LoadSource::Text(src.to_owned())))
+}
+
+/// Reads a file and parses it via [`from_str`], naming the file in errors.
+pub fn from_file<T, Context>(path: impl AsRef<std::path::Path>, ctx: &Context) -> Result<T, error::LoadError>
+where
+	T: for<'doc> FromKdlNode<'doc, Context>,
+	for<'doc> miette::Report: From<<T as FromKdlNode<'doc, Context>>::Error>,
+{
+	from_file_version(path, Some(version::Version::V2), ctx)
+}
+
+/// Reads a file and parses it via [`from_str_version`], naming the file in errors.
+pub fn from_file_version<T, Context>(
+	path: impl AsRef<std::path::Path>,
+	version: Option<version::Version>,
+	ctx: &Context,
+) -> Result<T, error::LoadError>
+where
+	T: for<'doc> FromKdlNode<'doc, Context>,
+	for<'doc> miette::Report: From<<T as FromKdlNode<'doc, Context>>::Error>,
+{
+	let path = path.as_ref();
+	let src = std::fs::read_to_string(path).map_err(|error| {
+		let kind = error::LoadErrorKind::Io {
+			path: path.to_owned(),
+			error,
+		};
+		error::LoadError::new(kind, error::LoadSource::None)
+	})?;
+	from_str_version(&src, version, ctx).map_err(|err| err.with_name(path.display().to_string()))
+}
+
+/// Writes a value as a document via its `AsKdlNode` impl, where the children of the value's node are
+/// the root nodes of the document. This is the inverse of [`from_str`].
+///
+/// A document has no entries of its own, so any arguments or properties of the value's node are not written.
+pub fn to_string<T: AsKdlNode + ?Sized>(value: &T) -> String {
+	let mut doc = value.as_kdl().into_document();
+	doc.autoformat();
+	doc.to_string()
+}
+
+/// Writes a value as a document via [`to_string`], in a specific version of KDL.
+pub fn to_string_version<T: AsKdlNode + ?Sized>(value: &T, version: version::Version) -> String {
+	let mut doc = value.as_kdl().into_document();
+	doc.autoformat();
+	version.convert(&mut doc);
+	doc.to_string()
+}
+
+/// Writes a value to a file via [`to_string`].
+pub fn to_file<T: AsKdlNode + ?Sized>(path: impl AsRef<std::path::Path>, value: &T) -> std::io::Result<()> {
+	std::fs::write(path, to_string(value))
+}
+
+/// Updates an existing document (e.g. one a user has edited by hand) to match a value, keeping the
+/// comments and formatting of any nodes and entries which did not change. See [`builder::Node::patch`].
+pub fn patch_document<T: AsKdlNode + ?Sized>(doc: &mut kdl::KdlDocument, value: &T) {
+	value.as_kdl().patch_document(doc);
+}
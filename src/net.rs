@@ -0,0 +1,129 @@
+//! Values for network addresses, which are written as strings. IP addresses are written with the
+//! type annotations reserved by KDL, `(ipv4)` and `(ipv6)`. CIDR networks are supported via the `ipnet` feature.
+
+use crate::{
+	error::{InvalidString, TypeAnnotationMismatch},
+	AsKdlValue, FromKdlValue,
+};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+const IPV4: &str = "ipv4";
+const IPV6: &str = "ipv6";
+
+impl_kdlvalue_parse!(Ipv4Addr, "an IPv4 address, e.g. 127.0.0.1", Some(IPV4));
+impl_kdlvalue_parse!(Ipv6Addr, "an IPv6 address, e.g. ::1", Some(IPV6));
+impl_kdlvalue_parse!(SocketAddr, "an IP address and port, e.g. 127.0.0.1:8080 or [::1]:8080");
+impl_kdlvalue_parse!(SocketAddrV4, "an IPv4 address and port, e.g. 127.0.0.1:8080");
+impl_kdlvalue_parse!(SocketAddrV6, "an IPv6 address and port, e.g. [::1]:8080");
+
+/// The annotation an address of either version is written with.
+fn ip_annotation(addr: &IpAddr) -> &'static str {
+	match addr {
+		IpAddr::V4(_) => IPV4,
+		IpAddr::V6(_) => IPV6,
+	}
+}
+
+impl<'doc> FromKdlValue<'doc> for IpAddr {
+	type Error = InvalidString;
+	fn from_kdl(value: &'doc kdl::KdlValue) -> Result<Self, Self::Error> {
+		crate::parse_str(value, "an IP address, e.g. 127.0.0.1 or ::1", str::parse::<IpAddr>)
+	}
+
+	/// Addresses of either version are read, but an annotated address must be of the annotated version.
+	fn from_kdl_typed(value: &'doc kdl::KdlValue, ty: Option<&'doc str>) -> Result<Self, Self::Error> {
+		let addr = Self::from_kdl(value)?;
+		match ty {
+			Some(ty) if ty != ip_annotation(&addr) => Err(TypeAnnotationMismatch {
+				expected: ip_annotation(&addr),
+				found: ty.to_owned(),
+			}
+			.into()),
+			_ => Ok(addr),
+		}
+	}
+}
+impl AsKdlValue for IpAddr {
+	fn as_kdl(&self) -> kdl::KdlValue {
+		kdl::KdlValue::String(self.to_string())
+	}
+
+	fn type_annotation(&self) -> Option<&'static str> {
+		Some(ip_annotation(self))
+	}
+}
+
+#[cfg(feature = "ipnet")]
+mod ipnet {
+	use ::ipnet::{IpNet, Ipv4Net, Ipv6Net};
+
+	impl_kdlvalue_parse!(IpNet, "a CIDR network, e.g. 10.0.0.0/8 or fd00::/8");
+	impl_kdlvalue_parse!(Ipv4Net, "an IPv4 CIDR network, e.g. 10.0.0.0/8");
+	impl_kdlvalue_parse!(Ipv6Net, "an IPv6 CIDR network, e.g. fd00::/8");
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{
+		builder::{Node, Value},
+		reader::EntryExt,
+	};
+
+	fn written<V: AsKdlValue>(value: V) -> String {
+		Node::default().with(Value(value)).build("node").to_string()
+	}
+
+	fn read<'doc, T>(doc: &'doc kdl::KdlDocument) -> Result<T, InvalidString>
+	where
+		T: FromKdlValue<'doc, Error = InvalidString>,
+	{
+		let entry = &doc.nodes()[0].entries()[0];
+		T::from_kdl_typed(entry.value(), entry.ty().map(kdl::KdlIdentifier::value))
+	}
+
+	fn doc(src: &str) -> kdl::KdlDocument {
+		src.parse().expect("valid kdl")
+	}
+
+	#[test]
+	fn ip_addr() {
+		let localhost = IpAddr::V4(Ipv4Addr::LOCALHOST);
+		assert_eq!(written(localhost), "node (ipv4)\"127.0.0.1\"\n");
+		assert_eq!(written(Ipv6Addr::LOCALHOST), "node (ipv6)::1\n");
+		assert_eq!(read(&doc("node (ipv4)\"127.0.0.1\"")), Ok(localhost));
+		assert_eq!(read(&doc("node \"::1\"")), Ok(IpAddr::V6(Ipv6Addr::LOCALHOST)));
+		assert_eq!(
+			read::<IpAddr>(&doc("node (ipv6)\"127.0.0.1\"")),
+			Err(InvalidString::TypeAnnotation(TypeAnnotationMismatch {
+				expected: "ipv4",
+				found: "ipv6".to_owned(),
+			}))
+		);
+		assert!(doc("node (ipv6)\"::1\"").nodes()[0].entries()[0]
+			.to::<Ipv4Addr>()
+			.is_err());
+		assert!(matches!(
+			read::<Ipv4Addr>(&doc("node \"256.0.0.1\"")),
+			Err(InvalidString::Malformed(_))
+		));
+	}
+
+	#[test]
+	fn socket_addr() {
+		let addr = SocketAddr::from((Ipv6Addr::LOCALHOST, 8080));
+		assert_eq!(written(addr), "node \"[::1]:8080\"\n");
+		assert_eq!(read(&doc("node \"[::1]:8080\"")), Ok(addr));
+		assert!(read::<SocketAddrV4>(&doc("node \"127.0.0.1\"")).is_err());
+	}
+
+	#[cfg(feature = "ipnet")]
+	#[test]
+	fn cidr() {
+		use ::ipnet::{IpNet, Ipv4Net};
+		let net = Ipv4Net::new(Ipv4Addr::new(10, 0, 0, 0), 8).unwrap();
+		assert_eq!(written(net), "node \"10.0.0.0/8\"\n");
+		assert_eq!(read(&doc("node \"10.0.0.0/8\"")), Ok(IpNet::V4(net)));
+		assert!(read::<Ipv4Net>(&doc("node \"10.0.0.0/33\"")).is_err());
+	}
+}
@@ -19,42 +19,256 @@
 	So I ended up with the more verbose exemplified in test module below.
 */
 
+use std::{
+	cell::RefCell,
+	collections::HashMap,
+	sync::{Arc, LazyLock, Mutex},
+};
+
 pub struct Node<'doc, Context> {
-	node: &'doc kdl::KdlNode,
+	/// The node being read, or `None` for the root of a document (see [`Document`]).
+	pub(crate) node: Option<&'doc kdl::KdlNode>,
+	children: Option<&'doc kdl::KdlDocument>,
 	ctx: &'doc Context,
 	is_child: bool,
 	entry_cursor: usize,
+	accessed: RefCell<Accessed>,
+	path: NodePath,
+	diagnostics: Option<Diagnostics>,
+}
+
+/// A sink for errors which are recorded while reading, so that every problem in a document can be
+/// reported at once instead of stopping at the first. Attached to a reader via [`Node::with_diagnostics`],
+/// and shared with all of the child readers it creates.
+#[derive(Clone, Default)]
+pub struct Diagnostics(Arc<Mutex<Vec<miette::Report>>>);
+
+impl Diagnostics {
+	fn lock(&self) -> std::sync::MutexGuard<'_, Vec<miette::Report>> {
+		self.0.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+	}
+
+	pub fn push(&self, err: impl Into<miette::Report>) {
+		let err = err.into();
+		// Errors which were already recorded are only returned to stop reading the node that failed.
+		if err.downcast_ref::<crate::error::Reported>().is_none() {
+			self.lock().push(err);
+		}
+	}
+
+	pub fn len(&self) -> usize {
+		self.lock().len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.lock().is_empty()
+	}
+
+	/// Combines the result of reading with the errors recorded while reading it.
+	/// Returns the value only if no errors were recorded, otherwise the single error or a report of all of them.
+	pub fn finish<T, E>(&self, result: Result<T, E>) -> Result<T, miette::Report>
+	where
+		miette::Report: From<E>,
+	{
+		let value = match result {
+			Ok(value) => Some(value),
+			Err(err) => {
+				self.push(miette::Report::from(err));
+				None
+			}
+		};
+		let mut errors = std::mem::take(&mut *self.lock());
+		match (value, errors.len()) {
+			(Some(value), 0) => Ok(value),
+			(_, 1) => Err(errors.remove(0)),
+			(_, 0) => Err(crate::error::Reported.into()),
+			(_, _) => Err(crate::error::MultipleErrors { errors }.into()),
+		}
+	}
+}
+
+/// The location of a node, and optionally one of its entries, within a document.
+/// Displayed as its ancestry, e.g. `document > server[1] > tls > cert@path`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodePath {
+	nodes: Vec<PathSegment>,
+	// Boxed to keep the errors which carry a path small.
+	entry: Option<Box<kdl::NodeKey>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct PathSegment {
+	name: String,
+	/// The index of the node among its siblings with the same name, if it has any.
+	index: Option<usize>,
+}
+
+impl NodePath {
+	fn root(node: &kdl::KdlNode) -> Self {
+		let nodes = vec![PathSegment {
+			name: node.name().value().to_owned(),
+			index: None,
+		}];
+		Self { nodes, entry: None }
+	}
+
+	fn child(&self, node: &kdl::KdlNode, index: Option<usize>) -> Self {
+		let mut path = self.clone();
+		path.entry = None;
+		path.nodes.push(PathSegment {
+			name: node.name().value().to_owned(),
+			index,
+		});
+		path
+	}
+
+	/// Returns this path, pointing at an entry of the node.
+	pub fn with_entry(&self, key: impl Into<kdl::NodeKey>) -> Self {
+		Self {
+			nodes: self.nodes.clone(),
+			entry: Some(Box::new(key.into())),
+		}
+	}
+
+	pub fn entry(&self) -> Option<&kdl::NodeKey> {
+		self.entry.as_deref()
+	}
+}
+
+impl std::fmt::Display for NodePath {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "document")?;
+		for segment in &self.nodes {
+			write!(f, " > {}", segment.name)?;
+			if let Some(index) = segment.index {
+				write!(f, "[{index}]")?;
+			}
+		}
+		match self.entry.as_deref() {
+			None => Ok(()),
+			Some(kdl::NodeKey::Index(idx)) => write!(f, "@{idx}"),
+			Some(kdl::NodeKey::Key(key)) => write!(f, "@{}", key.value()),
+		}
+	}
+}
+
+/// The names of properties and children which have been requested from a node, used by [`Node::finish`].
+#[derive(Clone, Default)]
+struct Accessed {
+	all_entries: bool,
+	all_children: bool,
+	props: Vec<String>,
+	children: Vec<String>,
 }
 
 impl<'doc, Context> Clone for Node<'doc, Context> {
 	fn clone(&self) -> Self {
 		Self {
 			node: self.node,
+			children: self.children,
 			ctx: self.ctx,
 			is_child: self.is_child,
-			entry_cursor: self.entry_cursor.clone(),
+			entry_cursor: self.entry_cursor,
+			accessed: self.accessed.clone(),
+			path: self.path.clone(),
+			diagnostics: self.diagnostics.clone(),
 		}
 	}
 }
 
-impl<'doc, Context> ToString for Node<'doc, Context> {
-	fn to_string(&self) -> String {
-		self.node.to_string()
+impl<'doc, Context> std::fmt::Display for Node<'doc, Context> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match (self.node, self.children) {
+			(Some(node), _) => write!(f, "{node}"),
+			(None, Some(doc)) => write!(f, "{doc}"),
+			(None, None) => Ok(()),
+		}
 	}
 }
 
 impl<'doc, Context> Node<'doc, Context> {
 	pub fn new(node: &'doc kdl::KdlNode, ctx: &'doc Context) -> Self {
 		Self {
-			node,
+			node: Some(node),
+			children: node.children(),
+			ctx,
+			is_child: false,
+			entry_cursor: 0,
+			accessed: Default::default(),
+			path: NodePath::root(node),
+			diagnostics: None,
+		}
+	}
+
+	fn new_root(doc: &'doc kdl::KdlDocument, ctx: &'doc Context) -> Self {
+		Self {
+			node: None,
+			children: Some(doc),
 			ctx,
 			is_child: false,
 			entry_cursor: 0,
+			accessed: Default::default(),
+			path: NodePath::default(),
+			diagnostics: None,
 		}
 	}
 
-	pub fn context(&self) -> &Context {
-		&self.ctx
+	fn new_child(node: &'doc kdl::KdlNode, index: Option<usize>, ctx: &'doc Context, parent: &ParentInfo) -> Self {
+		Self {
+			is_child: true,
+			path: parent.path.child(node, index),
+			diagnostics: parent.diagnostics.clone(),
+			..Self::new(node, ctx)
+		}
+	}
+
+	/// Records errors reported by this reader and its children to `diagnostics`, instead of returning them.
+	pub fn with_diagnostics(mut self, diagnostics: Diagnostics) -> Self {
+		self.diagnostics = Some(diagnostics);
+		self
+	}
+
+	pub fn diagnostics(&self) -> Option<&Diagnostics> {
+		self.diagnostics.as_ref()
+	}
+
+	/// Records a non-fatal error if this reader has a diagnostics sink, otherwise returns it.
+	pub fn report(&self, err: impl Into<miette::Report>) -> Result<(), miette::Report> {
+		match &self.diagnostics {
+			Some(diagnostics) => {
+				diagnostics.push(err);
+				Ok(())
+			}
+			None => Err(err.into()),
+		}
+	}
+
+	/// Collects every item of an iterator (such as the readers from `children(name).to::<T>()`),
+	/// reporting each failed item so that all items are attempted when this reader has a diagnostics sink.
+	pub fn collect_all<T, E, C>(&self, iter: impl Iterator<Item = Result<T, E>>) -> Result<C, miette::Report>
+	where
+		C: FromIterator<T>,
+		miette::Report: From<E>,
+	{
+		let mut items = Vec::new();
+		let mut failed = false;
+		for item in iter {
+			match item {
+				Ok(item) => items.push(item),
+				Err(err) => {
+					self.report(miette::Report::from(err))?;
+					failed = true;
+				}
+			}
+		}
+		match failed {
+			true => Err(crate::error::Reported.into()),
+			false => Ok(items.into_iter().collect()),
+		}
+	}
+
+	pub fn context(&self) -> &'doc Context {
+		self.ctx
 	}
 
 	pub fn is_child(&self) -> bool {
@@ -62,85 +276,328 @@ impl<'doc, Context> Node<'doc, Context> {
 	}
 
 	pub fn name(&self) -> &'doc kdl::KdlIdentifier {
-		self.node.name()
+		static DOCUMENT: LazyLock<kdl::KdlIdentifier> = LazyLock::new(|| "document".into());
+		match self.node {
+			Some(node) => node.name(),
+			None => &DOCUMENT,
+		}
+	}
+
+	fn span(&self) -> miette::SourceSpan {
+		match (self.node, self.children) {
+			(Some(node), _) => node.span(),
+			(None, Some(doc)) => doc.span(),
+			(None, None) => (0, 0).into(),
+		}
+	}
+
+	fn kdl_entries(&self) -> &'doc [kdl::KdlEntry] {
+		self.node.map_or(&[], kdl::KdlNode::entries)
+	}
+
+	fn kdl_entry(&self, key: impl Into<kdl::NodeKey>) -> Option<&'doc kdl::KdlEntry> {
+		self.node.and_then(|node| node.entry(key))
+	}
+
+	fn parent_info(&self) -> ParentInfo {
+		ParentInfo {
+			path: self.path.clone(),
+			diagnostics: self.diagnostics.clone(),
+		}
+	}
+
+	/// The location of this node within its document.
+	pub fn path(&self) -> &NodePath {
+		&self.path
+	}
+
+	/// The location of an entry of this node within its document.
+	pub fn entry_path(&self, entry: &kdl::KdlEntry) -> NodePath {
+		let key = match entry.name() {
+			Some(name) => kdl::NodeKey::Key(name.clone()),
+			None => {
+				let mut args = self.kdl_entries().iter().filter(|entry| entry.name().is_none());
+				kdl::NodeKey::Index(args.position(|arg| std::ptr::eq(arg, entry)).unwrap_or_default())
+			}
+		};
+		self.path.with_entry(key)
 	}
 
 	pub fn entries(&self) -> &'doc [kdl::KdlEntry] {
-		self.node.entries()
+		self.accessed.borrow_mut().all_entries = true;
+		self.kdl_entries()
 	}
 
 	pub fn document(&self) -> Result<&'doc kdl::KdlDocument, crate::error::MissingNodeDocument> {
-		match self.node.children() {
+		self.accessed.borrow_mut().all_children = true;
+		match self.children {
 			Some(doc) => Ok(doc),
-			None => {
-				let src = self.node.to_string();
-				Err(crate::error::MissingNodeDocument {
-					node_span: (0, src.len()).into(),
-					src,
-				})
-			}
+			None => Err(crate::error::MissingNodeDocument {
+				path: self.path.clone(),
+				node_span: self.span(),
+			}),
 		}
 	}
 
 	pub fn has_children(&self) -> bool {
-		let Ok(doc) = self.document() else { return false };
+		let Some(doc) = self.children else { return false };
 		!doc.nodes().is_empty()
 	}
 }
 
 impl<'doc, Context> Node<'doc, Context> {
 	pub fn peak(&self) -> Result<&'doc kdl::KdlEntry, crate::error::MissingEntry> {
-		let entry = self.node.entry(self.entry_cursor);
-		entry.ok_or_else(|| crate::error::MissingEntry::new_index(self.node.clone(), self.entry_cursor))
+		let entry = self.kdl_entry(self.entry_cursor);
+		entry.ok_or_else(|| crate::error::MissingEntry::new_index(self.span(), &self.path, self.entry_cursor))
 	}
 
+	#[allow(clippy::should_implement_trait)]
 	pub fn next(&mut self) -> Result<&'doc kdl::KdlEntry, crate::error::MissingEntry> {
-		let entry = self.node.entry(self.entry_cursor);
+		let entry = self.kdl_entry(self.entry_cursor);
 		if entry.is_some() {
 			self.entry_cursor += 1;
 		}
-		entry.ok_or_else(|| crate::error::MissingEntry::new_index(self.node.clone(), self.entry_cursor))
+		entry.ok_or_else(|| crate::error::MissingEntry::new_index(self.span(), &self.path, self.entry_cursor))
+	}
+
+	/// Reads the next positional argument as `T`, with the location of the argument in any error.
+	pub fn next_to<T>(&mut self) -> Result<T, crate::error::QueryError>
+	where
+		T: crate::FromKdlValue<'doc>,
+		miette::Report: From<T::Error>,
+	{
+		let entry = self.next()?;
+		Ok(entry.to::<T>().map_err(|err| err.with_path(self.entry_path(entry)))?)
+	}
+
+	/// The number of positional arguments which have not been read via [`Node::next`].
+	pub fn remaining_args(&self) -> usize {
+		let args = self.kdl_entries().iter().filter(|entry| entry.name().is_none());
+		args.count().saturating_sub(self.entry_cursor)
+	}
+
+	/// Reads all of the remaining positional arguments as `T`.
+	pub fn rest<T>(&mut self) -> Result<Vec<T>, FailedToParseValue>
+	where
+		T: crate::FromKdlValue<'doc>,
+		miette::Report: From<T::Error>,
+	{
+		let mut values = Vec::with_capacity(self.remaining_args());
+		while let Ok(entry) = self.next() {
+			let value = entry.to::<T>().map_err(|err| err.with_path(self.entry_path(entry)))?;
+			values.push(value);
+		}
+		Ok(values)
+	}
+
+	/// Reads all of the remaining positional arguments as a collection, e.g. `Vec<T>`, `HashSet<T>`,
+	/// or an array or tuple which must hold exactly as many values as there are arguments left.
+	pub fn args<C: crate::FromKdlArgs<'doc>>(&mut self) -> Result<C, crate::error::InvalidArgs> {
+		C::from_args(self)
+	}
+
+	/// Errors if the number of remaining positional arguments is not `expected`.
+	pub fn expect_args(&self, expected: usize) -> Result<(), crate::error::ArgsLength> {
+		let found = self.remaining_args();
+		match found == expected {
+			true => Ok(()),
+			false => Err(crate::error::ArgsLength {
+				path: self.path.clone(),
+				span: self.span(),
+				expected,
+				found,
+			}),
+		}
+	}
+
+	/// Reads the type annotation of the next positional argument without consuming it, e.g. the variant of an enum
+	/// written by [`builder::Node::with_arg_type`](crate::builder::Node::with_arg_type). If it is the last argument
+	/// and null, it only holds the annotation of a node without other positional arguments, and is consumed.
+	pub fn arg_type(&mut self) -> Result<&'doc str, crate::error::QueryError> {
+		let entry = self.peak()?;
+		let ty = entry.typed().map_err(|err| err.with_path(self.entry_path(entry)))?;
+		if entry.value().is_null() && self.remaining_args() == 1 {
+			self.next()?;
+		}
+		Ok(ty)
 	}
 
 	pub fn prop(&self, key: impl AsRef<str>) -> Result<&'doc kdl::KdlEntry, crate::error::MissingEntry> {
-		let entry = self.node.entry(key.as_ref());
-		entry.ok_or_else(|| crate::error::MissingEntry::new_prop(self.node.clone(), key))
+		self.accessed.borrow_mut().props.push(key.as_ref().to_owned());
+		let entry = self.kdl_entry(key.as_ref());
+		entry.ok_or_else(|| crate::error::MissingEntry::new_prop(self.span(), &self.path, key))
+	}
+
+	/// Reads a property as `T`, with the location of the property in any error.
+	pub fn prop_to<T>(&self, key: impl AsRef<str>) -> Result<T, crate::error::QueryError>
+	where
+		T: crate::FromKdlValue<'doc>,
+		miette::Report: From<T::Error>,
+	{
+		let entry = self.prop(key)?;
+		Ok(entry.to::<T>().map_err(|err| err.with_path(self.entry_path(entry)))?)
 	}
 
 	pub fn iter_children(&self) -> IterChildNodes<IterDocumentNodes<'doc>, &'doc Context> {
-		let iter_doc = self.document().ok().map(|doc| IterDocumentNodes(doc, 0));
-		IterChildNodes(iter_doc, &self.ctx)
+		self.accessed.borrow_mut().all_children = true;
+		let iter_doc = IterDocumentNodes::opt(self.children);
+		let siblings = SiblingIndices::new(self.children.into_iter().flat_map(|doc| doc.nodes()));
+		IterChildNodes(iter_doc, self.ctx, self.parent_info(), siblings)
 	}
 
 	pub fn children(
 		&self,
 		name: impl Into<kdl::KdlIdentifier>,
 	) -> IterChildNodes<IterDocumentNodesWithName<'doc>, &'doc Context> {
-		let iter_doc = self
-			.document()
-			.ok()
-			.map(|doc| IterDocumentNodesWithName(IterDocumentNodes(doc, 0), name.into()));
-		IterChildNodes(iter_doc, &self.ctx)
+		let name = name.into();
+		self.accessed.borrow_mut().children.push(name.value().to_owned());
+		let nodes = self.children.into_iter().flat_map(|doc| doc.nodes());
+		let siblings = SiblingIndices::new(nodes.filter(|node| node.name().value() == name.value()));
+		let iter_doc = IterDocumentNodes::opt(self.children).map(|iter| IterDocumentNodesWithName(iter, name));
+		IterChildNodes(iter_doc, self.ctx, self.parent_info(), siblings)
 	}
 
 	pub fn child(&self, key: impl Into<kdl::KdlIdentifier>) -> Result<Self, crate::error::NodeMissingChild> {
 		let key = key.into();
 		let child = self.children(key.clone()).next();
-		child.ok_or_else(|| {
-			let src = self.node.to_string();
-			let label = format!("missing child named {:?}", key.value());
-			let span = miette::LabeledSpan::new_primary_with_span(Some(label), (0, src.len()));
-			crate::error::NodeMissingChild {
-				src,
-				span,
-				child_name: key,
-			}
+		child.ok_or_else(|| crate::error::NodeMissingChild {
+			path: self.path.clone(),
+			span: self.span(),
+			child_name: key,
 		})
 	}
 
 	pub fn to<T: crate::FromKdlNode<'doc, Context>>(&mut self) -> Result<T, T::Error> {
 		T::from_kdl(self)
 	}
+
+	/// Returns the positional arguments, properties and children which have not been read from this node,
+	/// or `None` if everything was read. Only arguments before the cursor, and properties and children
+	/// which were requested by name (or via `entries`, `document` and `iter_children`), count as read.
+	pub fn unconsumed(&self) -> Option<crate::error::Unconsumed> {
+		use crate::error::{Unused, UnusedKind};
+		let accessed = self.accessed.borrow();
+		let mut unused = Vec::new();
+		if !accessed.all_entries {
+			let mut arg_idx = 0;
+			for entry in self.kdl_entries() {
+				let kind = match entry.name() {
+					Some(name) if accessed.props.iter().any(|prop| prop == name.value()) => continue,
+					Some(name) => UnusedKind::Property(name.value().to_owned()),
+					None => {
+						arg_idx += 1;
+						if arg_idx <= self.entry_cursor {
+							continue;
+						}
+						UnusedKind::Argument(arg_idx - 1)
+					}
+				};
+				unused.push(Unused::new(kind, entry.span(), &accessed.props));
+			}
+		}
+		if let (false, Some(doc)) = (accessed.all_children, self.children) {
+			for child in doc.nodes() {
+				let name = child.name().value();
+				if !accessed.children.iter().any(|accessed| accessed == name) {
+					let kind = UnusedKind::Child(name.to_owned());
+					unused.push(Unused::new(kind, child.span(), &accessed.children));
+				}
+			}
+		}
+		match unused.is_empty() {
+			true => None,
+			false => Some(crate::error::Unconsumed {
+				path: self.path.clone(),
+				span: self.node.map_or_else(|| self.span(), |node| node.name().span()),
+				unused,
+			}),
+		}
+	}
+
+	/// Errors if any positional arguments, properties or children of this node were not read.
+	pub fn finish(&self) -> Result<(), crate::error::Unconsumed> {
+		match self.unconsumed() {
+			None => Ok(()),
+			Some(err) => Err(err),
+		}
+	}
+}
+
+/// Reads the nodes at the root of a document, with the same API as reading the children of a [`Node`].
+/// Types which are read from a node's children (e.g. derived `FromKdlNode` impls with only `child` fields)
+/// can be read from a whole document via [`Document::to`].
+pub struct Document<'doc, Context>(Node<'doc, Context>);
+
+impl<'doc, Context> Clone for Document<'doc, Context> {
+	fn clone(&self) -> Self {
+		Self(self.0.clone())
+	}
+}
+
+impl<'doc, Context> std::fmt::Display for Document<'doc, Context> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		self.0.fmt(f)
+	}
+}
+
+impl<'doc, Context> Document<'doc, Context> {
+	pub fn new(doc: &'doc kdl::KdlDocument, ctx: &'doc Context) -> Self {
+		Self(Node::new_root(doc, ctx))
+	}
+
+	/// Records errors reported while reading the document to `diagnostics`, see [`Node::with_diagnostics`].
+	pub fn with_diagnostics(self, diagnostics: Diagnostics) -> Self {
+		Self(self.0.with_diagnostics(diagnostics))
+	}
+
+	pub fn diagnostics(&self) -> Option<&Diagnostics> {
+		self.0.diagnostics()
+	}
+
+	pub fn context(&self) -> &'doc Context {
+		self.0.context()
+	}
+
+	pub fn document(&self) -> &'doc kdl::KdlDocument {
+		self.0.children.expect("documents always have children")
+	}
+
+	pub fn has_children(&self) -> bool {
+		self.0.has_children()
+	}
+
+	pub fn iter_children(&self) -> IterChildNodes<IterDocumentNodes<'doc>, &'doc Context> {
+		self.0.iter_children()
+	}
+
+	pub fn children(
+		&self,
+		name: impl Into<kdl::KdlIdentifier>,
+	) -> IterChildNodes<IterDocumentNodesWithName<'doc>, &'doc Context> {
+		self.0.children(name)
+	}
+
+	pub fn child(
+		&self,
+		key: impl Into<kdl::KdlIdentifier>,
+	) -> Result<Node<'doc, Context>, crate::error::NodeMissingChild> {
+		self.0.child(key)
+	}
+
+	/// Reads the document as a node whose children are the nodes of the document.
+	pub fn to<T: crate::FromKdlNode<'doc, Context>>(&mut self) -> Result<T, T::Error> {
+		self.0.to::<T>()
+	}
+
+	/// The root nodes which have not been read, see [`Node::unconsumed`].
+	pub fn unconsumed(&self) -> Option<crate::error::Unconsumed> {
+		self.0.unconsumed()
+	}
+
+	/// Errors if any root nodes were not read, see [`Node::finish`].
+	pub fn finish(&self) -> Result<(), crate::error::Unconsumed> {
+		self.0.finish()
+	}
 }
 
 pub struct IterDocumentNodes<'doc>(&'doc kdl::KdlDocument, usize);
@@ -162,40 +619,56 @@ pub struct IterDocumentNodesWithName<'doc>(IterDocumentNodes<'doc>, kdl::KdlIden
 impl<'doc> Iterator for IterDocumentNodesWithName<'doc> {
 	type Item = &'doc kdl::KdlNode;
 	fn next(&mut self) -> Option<Self::Item> {
-		while let Some(node) = self.0.next() {
-			if node.name().value() == self.1.value() {
-				return Some(node);
-			}
+		let name = self.1.value();
+		self.0.find(|node| node.name().value() == name)
+	}
+}
+
+pub struct IterChildNodes<Iter, Context>(Option<Iter>, Context, ParentInfo, SiblingIndices);
+
+/// The index of each node among its siblings with the same name, counted once when iterating starts.
+struct SiblingIndices(HashMap<String, SiblingCount>);
+#[derive(Default)]
+struct SiblingCount {
+	total: usize,
+	visited: usize,
+}
+impl SiblingIndices {
+	fn new<'doc>(nodes: impl Iterator<Item = &'doc kdl::KdlNode>) -> Self {
+		let mut counts = HashMap::<String, SiblingCount>::new();
+		for node in nodes {
+			counts.entry(node.name().value().to_owned()).or_default().total += 1;
 		}
-		None
+		Self(counts)
+	}
+
+	/// The index of the next node visited with this name, if it has any siblings with the same name.
+	fn next(&mut self, node: &kdl::KdlNode) -> Option<usize> {
+		let count = self.0.get_mut(node.name().value())?;
+		count.visited += 1;
+		(count.total > 1).then_some(count.visited - 1)
 	}
 }
 
-pub struct IterChildNodes<Iter, Context>(Option<Iter>, Context);
+/// The state of a reader which is passed down to the readers of its children.
+struct ParentInfo {
+	path: NodePath,
+	diagnostics: Option<Diagnostics>,
+}
 impl<'doc, Context: 'doc> Iterator for IterChildNodes<IterDocumentNodes<'doc>, &'doc Context> {
 	type Item = Node<'doc, Context>;
 	fn next(&mut self) -> Option<Self::Item> {
-		let iter_doc = self.0.as_mut()?;
-		let node = iter_doc.next()?;
-		Some(Node {
-			node,
-			ctx: self.1,
-			is_child: true,
-			entry_cursor: 0,
-		})
+		let node = self.0.as_mut()?.next()?;
+		let index = self.3.next(node);
+		Some(Node::new_child(node, index, self.1, &self.2))
 	}
 }
 impl<'doc, Context: 'doc> Iterator for IterChildNodes<IterDocumentNodesWithName<'doc>, &'doc Context> {
 	type Item = Node<'doc, Context>;
 	fn next(&mut self) -> Option<Self::Item> {
-		let iter_doc = self.0.as_mut()?;
-		let node = iter_doc.next()?;
-		Some(Node {
-			node,
-			ctx: self.1,
-			is_child: true,
-			entry_cursor: 0,
-		})
+		let node = self.0.as_mut()?.next()?;
+		let index = self.3.next(node);
+		Some(Node::new_child(node, index, self.1, &self.2))
 	}
 }
 
@@ -212,10 +685,15 @@ where
 	}
 
 	pub fn to<T: crate::FromKdlNode<'doc, Context>>(self) -> IterNodeTyped<Self, T> {
-		IterNodeTyped(self, std::marker::PhantomData::default())
+		IterNodeTyped(self, std::marker::PhantomData)
 	}
 }
 
+/// An iterator over one entry of each node, which can also yield the location of each entry.
+pub trait IterEntries<'doc>: Iterator<Item = Result<&'doc kdl::KdlEntry, crate::error::MissingEntry>> {
+	fn next_with_path(&mut self) -> Option<Result<(&'doc kdl::KdlEntry, NodePath), crate::error::MissingEntry>>;
+}
+
 pub struct IterNodeFirstValue<Iter>(Iter);
 impl<'doc, Context: 'doc, Iter> Iterator for IterNodeFirstValue<Iter>
 where
@@ -227,12 +705,21 @@ where
 		Some(node.next())
 	}
 }
+impl<'doc, Context: 'doc, Iter> IterEntries<'doc> for IterNodeFirstValue<Iter>
+where
+	Iter: Iterator<Item = Node<'doc, Context>>,
+{
+	fn next_with_path(&mut self) -> Option<Result<(&'doc kdl::KdlEntry, NodePath), crate::error::MissingEntry>> {
+		let mut node = self.0.next()?;
+		Some(node.next().map(|entry| (entry, node.entry_path(entry))))
+	}
+}
 impl<'doc, Context: 'doc, Iter> IterNodeFirstValue<Iter>
 where
 	Iter: Iterator<Item = Node<'doc, Context>>,
 {
 	pub fn to<T: crate::FromKdlValue<'doc>>(self) -> IterNodeValueTyped<Self, T> {
-		IterNodeValueTyped(self, std::marker::PhantomData::default())
+		IterNodeValueTyped(self, std::marker::PhantomData)
 	}
 }
 
@@ -247,27 +734,36 @@ where
 		Some(node.prop(self.1.as_ref()))
 	}
 }
+impl<'doc, Context: 'doc, Iter, S: AsRef<str>> IterEntries<'doc> for IterNodePropValue<Iter, S>
+where
+	Iter: Iterator<Item = Node<'doc, Context>>,
+{
+	fn next_with_path(&mut self) -> Option<Result<(&'doc kdl::KdlEntry, NodePath), crate::error::MissingEntry>> {
+		let node = self.0.next()?;
+		Some(node.prop(self.1.as_ref()).map(|entry| (entry, node.entry_path(entry))))
+	}
+}
 impl<'doc, Context: 'doc, Iter, S: AsRef<str>> IterNodePropValue<Iter, S>
 where
 	Iter: Iterator<Item = Node<'doc, Context>>,
 {
 	pub fn to<T: crate::FromKdlValue<'doc>>(self) -> IterNodeValueTyped<Self, T> {
-		IterNodeValueTyped(self, std::marker::PhantomData::default())
+		IterNodeValueTyped(self, std::marker::PhantomData)
 	}
 }
 
 pub struct IterNodeValueTyped<Iter, T>(Iter, std::marker::PhantomData<T>);
 impl<'doc, Iter, T> Iterator for IterNodeValueTyped<Iter, T>
 where
-	Iter: Iterator<Item = Result<&'doc kdl::KdlEntry, crate::error::MissingEntry>>,
+	Iter: IterEntries<'doc>,
 	T: crate::FromKdlValue<'doc>,
 	miette::Report: From<T::Error>,
 {
 	type Item = Result<Result<T, FailedToParseValue>, crate::error::MissingEntry>;
 	fn next(&mut self) -> Option<Self::Item> {
-		match self.0.next()? {
+		match self.0.next_with_path()? {
 			Err(missing_entry) => Some(Err(missing_entry)),
-			Ok(entry) => Some(Ok(entry.to::<T>())),
+			Ok((entry, path)) => Some(Ok(entry.to::<T>().map_err(|err| err.with_path(path)))),
 		}
 	}
 }
@@ -318,15 +814,11 @@ impl<'doc> EntryExt<'doc> for kdl::KdlEntry {
 	fn typed(&'doc self) -> Result<&'doc str, crate::error::MissingEntryType> {
 		match self.ty().map(kdl::KdlIdentifier::value) {
 			Some(value) => Ok(value),
-			None => {
-				// TODO: Would be nice if the source was the node itself
-				let src = self.to_string();
-				Err(crate::error::MissingEntryType {
-					span: (0, src.len()).into(),
-					src,
-					value: self.clone(),
-				})
-			}
+			None => Err(crate::error::MissingEntryType {
+				span: self.span(),
+				value: Box::new(self.clone()),
+				path: None,
+			}),
 		}
 	}
 
@@ -335,12 +827,26 @@ impl<'doc> EntryExt<'doc> for kdl::KdlEntry {
 		T: crate::FromKdlValue<'doc>,
 		miette::Report: From<T::Error>,
 	{
-		let result = T::from_kdl(self.value());
+		if let (Some(expected), Some(found)) = (T::type_annotation(), self.ty()) {
+			if found.value() != expected {
+				let mismatch = crate::error::TypeAnnotationMismatch {
+					expected,
+					found: found.value().to_owned(),
+				};
+				return Err(FailedToParseValue {
+					span: self.span(),
+					err: miette::Report::new(mismatch),
+					path: None,
+				});
+			}
+		}
+		let result = T::from_kdl_typed(self.value(), self.ty().map(kdl::KdlIdentifier::value));
 		let parsed_value = result.map_err(|err| {
 			let span = self.span();
 			FailedToParseValue {
 				span,
 				err: miette::Report::from(err),
+				path: None,
 			}
 		})?;
 		Ok(parsed_value)
@@ -348,21 +854,45 @@ impl<'doc> EntryExt<'doc> for kdl::KdlEntry {
 }
 
 #[derive(thiserror::Error, Debug)]
-#[error("Failed to parse value: {err:?}")]
 pub struct FailedToParseValue {
 	span: miette::SourceSpan,
 	err: miette::Report,
+	path: Option<NodePath>,
+}
+impl FailedToParseValue {
+	/// Attaches the location of the entry which failed to parse, see [`Node::entry_path`].
+	pub fn with_path(mut self, path: NodePath) -> Self {
+		self.path = Some(path);
+		self
+	}
+}
+impl std::fmt::Display for FailedToParseValue {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match &self.path {
+			Some(path) => write!(f, "Failed to parse value at {path}: {:?}", self.err),
+			None => write!(f, "Failed to parse value: {:?}", self.err),
+		}
+	}
+}
+impl PartialEq for FailedToParseValue {
+	fn eq(&self, other: &Self) -> bool {
+		self.span == other.span && self.err.to_string() == other.err.to_string() && self.path == other.path
+	}
 }
 impl miette::Diagnostic for FailedToParseValue {
 	fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
 		Some(Box::new("kdlize::failed_to_parse_value"))
 	}
 
+	fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+		self.err.help()
+	}
+
 	fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
 		Some(Box::new(
 			vec![miette::LabeledSpan::new_with_span(
 				Some(format!("{}", self.err)),
-				self.span.clone(),
+				self.span,
 			)]
 			.into_iter(),
 		))
@@ -382,15 +912,7 @@ impl<'doc> EntryOptExt<'doc> for Option<&'doc kdl::KdlEntry> {
 		miette::Report: From<T::Error>, // T::Error: miette::Diagnostic + Send + Sync + 'static,
 	{
 		let Some(entry) = self else { return Ok(None) };
-		let result = T::from_kdl(entry.value());
-		let parsed_value = result.map_err(|err| {
-			let span = entry.span();
-			FailedToParseValue {
-				span,
-				err: miette::Report::from(err),
-			}
-		})?;
-		Ok(Some(parsed_value))
+		Ok(Some(entry.to::<T>()?))
 	}
 }
 
@@ -407,12 +929,12 @@ impl<'doc, Context> NodeOptExt<'doc> for Option<Node<'doc, Context>> {
 
 	fn next(self) -> Result<Option<&'doc kdl::KdlEntry>, crate::error::MissingEntry> {
 		let Some(mut node) = self else { return Ok(None) };
-		node.next().map(|v| Some(v))
+		node.next().map(Some)
 	}
 
 	fn prop(self, key: impl AsRef<str>) -> Result<Option<&'doc kdl::KdlEntry>, crate::error::MissingEntry> {
 		let Some(node) = self else { return Ok(None) };
-		node.prop(key).map(|v| Some(v))
+		node.prop(key).map(Some)
 	}
 
 	fn to<T>(self) -> Result<Option<T>, T::Error>
@@ -425,20 +947,20 @@ impl<'doc, Context> NodeOptExt<'doc> for Option<Node<'doc, Context>> {
 }
 
 pub trait DocumentExt {
-	fn iter_children<'doc>(&'doc self) -> IterDocumentNodes<'doc>;
-	fn children<'doc>(&'doc self, name: impl Into<kdl::KdlIdentifier>) -> IterDocumentNodesWithName<'doc>;
-	fn child<'doc>(&'doc self, key: impl Into<kdl::KdlIdentifier>) -> Option<&'doc kdl::KdlNode>;
+	fn iter_children(&self) -> IterDocumentNodes<'_>;
+	fn children(&self, name: impl Into<kdl::KdlIdentifier>) -> IterDocumentNodesWithName<'_>;
+	fn child(&self, key: impl Into<kdl::KdlIdentifier>) -> Option<&kdl::KdlNode>;
 }
 impl DocumentExt for kdl::KdlDocument {
-	fn iter_children<'doc>(&'doc self) -> IterDocumentNodes<'doc> {
+	fn iter_children(&self) -> IterDocumentNodes<'_> {
 		IterDocumentNodes(self, 0)
 	}
 
-	fn children<'doc>(&'doc self, name: impl Into<kdl::KdlIdentifier>) -> IterDocumentNodesWithName<'doc> {
+	fn children(&self, name: impl Into<kdl::KdlIdentifier>) -> IterDocumentNodesWithName<'_> {
 		IterDocumentNodesWithName(IterDocumentNodes(self, 0), name.into())
 	}
 
-	fn child<'doc>(&'doc self, key: impl Into<kdl::KdlIdentifier>) -> Option<&'doc kdl::KdlNode> {
+	fn child(&self, key: impl Into<kdl::KdlIdentifier>) -> Option<&kdl::KdlNode> {
 		let key = key.into();
 		self.children(key.clone()).next()
 	}
@@ -515,6 +1037,7 @@ mod test {
 	}
 
 	#[test]
+	#[allow(clippy::bool_assert_comparison)]
 	fn next_value() -> Result<(), miette::Error> {
 		let node = node();
 		let mut reader = Node::new(&node, &());
@@ -547,7 +1070,7 @@ mod test {
 			});
 			node
 		};
-		assert_eq!(value.node, &expected);
+		assert_eq!(value.node, Some(&expected));
 		Ok(())
 	}
 
@@ -566,7 +1089,7 @@ mod test {
 		let reader = Node::new(&node, &());
 		let mut iter = reader.children("child2");
 		assert_eq!(
-			iter.next().map(|reader| reader.node),
+			iter.next().and_then(|reader| reader.node),
 			Some(&{
 				let mut node = kdl::KdlNode::new("child2");
 				node.entries_mut().push(kdl::KdlEntry::new("ValueA"));
@@ -575,7 +1098,7 @@ mod test {
 			})
 		);
 		assert_eq!(
-			iter.next().map(|reader| reader.node),
+			iter.next().and_then(|reader| reader.node),
 			Some(&{
 				let mut node = kdl::KdlNode::new("child2");
 				node.entries_mut().push(kdl::KdlEntry::new("ValueB"));
@@ -583,7 +1106,163 @@ mod test {
 				node
 			})
 		);
-		assert_eq!(iter.next().map(|reader| reader.node), None);
+		assert_eq!(iter.next().and_then(|reader| reader.node), None);
+	}
+
+	#[test]
+	fn error_source_spans() {
+		let src = "first 1\nserver port=80 {\n\ttls\n}\n";
+		let doc = src.parse::<kdl::KdlDocument>().expect("valid kdl");
+		let reader = Node::new(doc.child("server").expect("has server"), &());
+		let span = reader.prop("host").expect_err("missing prop").span;
+		assert_eq!(&src[span.offset()..span.offset() + 6], "server");
+		let Err(err) = reader.child("tls").and_then(|tls| tls.child("cert")) else {
+			panic!("cert is missing");
+		};
+		assert_eq!(&src[err.span.offset()..err.span.offset() + err.span.len()], "tls");
+
+		let report = miette::Report::new(err).with_source_code(miette::NamedSource::new("config.kdl", src.to_owned()));
+		let mut rendered = String::new();
+		miette::NarratableReportHandler::new()
+			.render_report(&mut rendered, report.as_ref())
+			.expect("renders");
+		assert!(rendered.contains("snippet for config.kdl"), "{rendered}");
+		assert!(rendered.contains("label at line 3, columns 2 to 4"), "{rendered}");
+	}
+
+	#[test]
+	fn unconsumed() {
+		use crate::error::UnusedKind;
+		let src = "style 1 2 colr=red size=3 {\n\tbordr\n\tmargin\n}";
+		let doc = src.parse::<kdl::KdlDocument>().expect("valid kdl");
+		let mut reader = Node::new(&doc.nodes()[0], &());
+		let _ = reader.next();
+		let _ = reader.prop("color");
+		let _ = reader.prop("size");
+		let _ = reader.child("border");
+		let err = reader.finish().expect_err("has unused entries");
+		let unused = err
+			.unused
+			.iter()
+			.map(|unused| (&unused.kind, unused.suggestion.as_deref()));
+		assert_eq!(
+			unused.collect::<Vec<_>>(),
+			vec![
+				(&UnusedKind::Argument(1), None),
+				(&UnusedKind::Property("colr".into()), Some("did you mean \"color\"?")),
+				(&UnusedKind::Child("bordr".into()), Some("did you mean \"border\"?")),
+				(&UnusedKind::Child("margin".into()), None),
+			]
+		);
+		let span = err.unused[1].span;
+		assert_eq!(&src[span.offset()..span.offset() + span.len()], "colr=red");
+
+		let _ = reader.next();
+		let _ = reader.iter_children();
+		let _ = reader.prop("colr");
+		assert_eq!(reader.finish(), Ok(()));
+	}
+
+	#[test]
+	fn error_paths() {
+		let node = node();
+		let reader = Node::new(&node, &());
+		let Ok(child) = reader.child("child1") else {
+			panic!("has child1");
+		};
+		let err = child.prop("missing").map_err(|err| err.path.to_string());
+		assert_eq!(err, Err("document > node > child1".into()));
+
+		let second = reader.children("child2").nth(1).expect("has two child2 nodes");
+		assert_eq!(second.path().to_string(), "document > node > child2[1]");
+		let flag = second.prop("flag").expect("has flag");
+		assert_eq!(second.entry_path(flag).to_string(), "document > node > child2[1]@flag");
+		let value = reader
+			.iter_children()
+			.next()
+			.and_then(|mut child| child.next().ok().map(|arg| child.entry_path(arg)));
+		assert_eq!(
+			value.map(|path| path.to_string()),
+			Some("document > node > child1@0".into())
+		);
+
+		// Values which fail to parse carry the location of their entry.
+		let parse_path = |err: crate::error::QueryError| match err {
+			crate::error::QueryError::ParseValue(err) => err.path.map(|path| path.to_string()),
+			_ => None,
+		};
+		assert_eq!(
+			second.prop_to::<u32>("flag").map_err(parse_path),
+			Err(Some("document > node > child2[1]@flag".into()))
+		);
+		let mut child = reader.child("child1").expect("has child1");
+		assert_eq!(child.next_to::<u32>(), Ok(42));
+		assert_eq!(
+			child.next_to::<u32>().map_err(parse_path),
+			Err(Some("document > node > child1@1".into()))
+		);
+		let values = reader.children("child2").value().to::<u32>();
+		let paths = values.map(|value| {
+			value
+				.expect("has an argument")
+				.map_err(|err| err.path.map(|path| path.to_string()))
+		});
+		assert_eq!(
+			paths.collect::<Vec<_>>(),
+			[
+				Err(Some("document > node > child2[0]@0".into())),
+				Err(Some("document > node > child2[1]@0".into()))
+			]
+		);
+
+		let paths = reader.iter_children().map(|child| child.path().to_string());
+		assert_eq!(
+			paths.collect::<Vec<_>>(),
+			[
+				"document > node > child1",
+				"document > node > empty",
+				"document > node > child2[0]",
+				"document > node > child2[1]"
+			]
+		);
+	}
+
+	#[test]
+	fn diagnostics() {
+		let node = node();
+		let reader = Node::new(&node, &());
+		assert!(reader.report(crate::error::Reported).is_err());
+
+		let diagnostics = Diagnostics::default();
+		let reader = reader.with_diagnostics(diagnostics.clone());
+		let child = reader.child("child1").ok();
+		assert!(child.is_some_and(|child| child.report(child.prop("missing").unwrap_err()).is_ok()));
+		assert!(reader.report(crate::error::Reported).is_ok());
+		assert_eq!(diagnostics.len(), 1);
+
+		let err = diagnostics
+			.finish(Ok::<_, miette::Report>(()))
+			.expect_err("an error was reported");
+		assert!(err.downcast_ref::<crate::error::MissingEntry>().is_some());
+		assert!(diagnostics.is_empty());
+	}
+
+	#[test]
+	fn document_root() -> Result<(), miette::Error> {
+		let doc = "name example\nserver 1\nserver 2\nextra".parse::<kdl::KdlDocument>()?;
+		let reader = Document::new(&doc, &());
+		assert_eq!(reader.child("name")?.next()?.to::<&str>()?, "example");
+		let ports = reader.children("server").value().to::<u16>().collect::<Vec<_>>()??;
+		assert_eq!(ports, vec![1, 2]);
+		assert_eq!(
+			reader.children("server").nth(1).map(|node| node.path().to_string()),
+			Some("document > server[1]".into())
+		);
+
+		let err = reader.finish().expect_err("extra was not read");
+		assert_eq!(err.unused.len(), 1);
+		assert!(matches!(reader.child("missing"), Err(err) if err.path.to_string() == "document"));
+		Ok(())
 	}
 
 	#[test]
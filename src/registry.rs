@@ -0,0 +1,127 @@
+//! Reading and writing trait objects (e.g. `Box<dyn Component>`), where each node is tagged with the
+//! [`NodeId::id`] of the type it is read as.
+
+use crate::{
+	builder::{self, IntoNodeBuilder, NodeComponent},
+	reader, AsKdlNode, FromKdlNode, NodeId,
+};
+use std::collections::BTreeMap;
+
+/// Where a node stores the id of its type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tag {
+	/// The name of the node itself, e.g. `circle 5.0`. Written by [`ChildById`].
+	#[default]
+	Name,
+	/// The type annotation of the first positional argument, e.g. `shape (circle)5.0`, like the `tag = "type"`
+	/// enums of the derive macros. Written by [`TypedById`].
+	Type,
+}
+
+type ReadFn<Dyn, Context> =
+	Box<dyn for<'doc> Fn(&mut reader::Node<'doc, Context>) -> Result<Box<Dyn>, miette::Report> + Send + Sync>;
+
+/// Reads trait objects `Box<Dyn>` by looking up the id a node is tagged with, and reading the registered type.
+/// Types are registered by their [`NodeId::id`], so that [`NodeId::get_id`] writes the id they are read by.
+///
+/// A registry is provided by the reader's context via [`HasRegistry`], which lets `Box<Dyn>` be read
+/// like any other type, e.g. `node.to::<Box<dyn Component>>()`.
+pub struct Registry<Dyn: ?Sized, Context> {
+	tag: Tag,
+	readers: BTreeMap<&'static str, ReadFn<Dyn, Context>>,
+}
+
+impl<Dyn: ?Sized + 'static, Context> Default for Registry<Dyn, Context> {
+	fn default() -> Self {
+		Self::new(Tag::default())
+	}
+}
+
+impl<Dyn: ?Sized + 'static, Context> Registry<Dyn, Context> {
+	pub fn new(tag: Tag) -> Self {
+		Self {
+			tag,
+			readers: BTreeMap::new(),
+		}
+	}
+
+	pub fn tag(&self) -> Tag {
+		self.tag
+	}
+
+	/// Registers a type to be read when a node is tagged with its id, where `into_dyn` converts it into
+	/// the trait object (usually `|value| Box::new(value)`). A type registered with the same id is replaced.
+	pub fn register<T>(&mut self, into_dyn: fn(T) -> Box<Dyn>) -> &mut Self
+	where
+		T: NodeId + for<'doc> FromKdlNode<'doc, Context> + 'static,
+		for<'doc> miette::Report: From<<T as FromKdlNode<'doc, Context>>::Error>,
+	{
+		self.readers
+			.insert(T::id(), Box::new(move |node| Ok(into_dyn(node.to::<T>()?))));
+		self
+	}
+
+	pub fn with<T>(mut self, into_dyn: fn(T) -> Box<Dyn>) -> Self
+	where
+		T: NodeId + for<'doc> FromKdlNode<'doc, Context> + 'static,
+		for<'doc> miette::Report: From<<T as FromKdlNode<'doc, Context>>::Error>,
+	{
+		self.register(into_dyn);
+		self
+	}
+
+	/// The ids of all registered types, in sorted order.
+	pub fn ids(&self) -> impl Iterator<Item = &'static str> + '_ {
+		self.readers.keys().copied()
+	}
+
+	pub fn contains(&self, id: &str) -> bool {
+		self.readers.contains_key(id)
+	}
+
+	/// Reads the type registered for the id the node is tagged with.
+	pub fn read(&self, node: &mut reader::Node<'_, Context>) -> Result<Box<Dyn>, miette::Report> {
+		let (id, span) = match self.tag {
+			Tag::Name => (node.name().value(), node.name().span()),
+			Tag::Type => {
+				let span = node.peak()?.span();
+				(node.arg_type()?, span)
+			}
+		};
+		let Some(read) = self.readers.get(id) else {
+			let expected = self.ids().map(str::to_owned).collect();
+			return Err(crate::error::UnknownNodeId::new(node.path(), span, id, expected).into());
+		};
+		read(node)
+	}
+}
+
+/// A reader context which can read trait objects `Box<Dyn>` via its [`Registry`].
+pub trait HasRegistry<Dyn: ?Sized>: Sized {
+	fn registry(&self) -> &Registry<Dyn, Self>;
+}
+
+impl<'doc, Dyn: ?Sized + 'static, Context: HasRegistry<Dyn>> FromKdlNode<'doc, Context> for Box<Dyn> {
+	type Error = miette::Report;
+	fn from_kdl(node: &mut reader::Node<'doc, Context>) -> Result<Self, Self::Error> {
+		let ctx = node.context();
+		ctx.registry().read(node)
+	}
+}
+
+/// Writes a value as a child node named by its [`NodeId::get_id`], to be read by a registry with [`Tag::Name`].
+pub struct ChildById<'a, V: ?Sized>(pub &'a V);
+impl<V: NodeId + AsKdlNode + ?Sized> NodeComponent for ChildById<'_, V> {
+	fn apply_to(self, builder: &mut builder::Node) {
+		self.0.as_kdl().build(self.0.get_id()).apply_to(builder);
+	}
+}
+
+/// Writes a value with its [`NodeId::get_id`] as the type annotation of its first positional argument,
+/// to be read by a registry with [`Tag::Type`], e.g. `Child("shape", TypedById(&value))`.
+pub struct TypedById<'a, V: ?Sized>(pub &'a V);
+impl<V: NodeId + AsKdlNode + ?Sized> IntoNodeBuilder for TypedById<'_, V> {
+	fn into_node(self) -> builder::Node {
+		self.0.as_kdl().with_arg_type(self.0.get_id())
+	}
+}
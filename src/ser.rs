@@ -0,0 +1,522 @@
+//! A [`serde::Serializer`] which builds KDL documents and nodes, enabled by the `serde` feature.
+//!
+//! Values are written with the same mapping that [`crate::de`] reads, using the `Property`, `Child`
+//! and `Children` components of [`crate::builder`]:
+//! - A struct or map is a node whose primitive fields are properties, and whose other fields are children.
+//!   At the top level of a document, every field is a node (primitive fields are a node with one argument).
+//! - A sequence of primitives is a child node whose positional arguments are the values (`tags "a" "b"`).
+//! - A sequence of structs is one child node per element, each named after the field (`Children`).
+//! - A unit variant is its name as a string, other variants are a child node named after the variant.
+//! - `None` and unit fields are omitted. A `None` element of a sequence is a `#null` argument, or a `- #null`
+//!   child among elements which are not primitives, so that the other elements keep their positions.
+
+use crate::builder::{self, Child, Children, Property, Value};
+use serde::ser::{self, Serialize};
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, miette::Diagnostic)]
+#[error("{0}")]
+#[diagnostic(code(kdlize::serialize))]
+pub struct Error(String);
+impl ser::Error for Error {
+	fn custom<T: std::fmt::Display>(msg: T) -> Self {
+		Self(msg.to_string())
+	}
+}
+
+/// Serializes a struct or map into a node builder, whose fields are written as properties and children.
+pub fn to_node<T: Serialize + ?Sized>(value: &T) -> Result<builder::Node, Error> {
+	Ok(value.serialize(Serializer)?.into_node())
+}
+
+/// Serializes a struct or map into a document, whose fields are written as top-level nodes.
+pub fn to_document<T: Serialize + ?Sized>(value: &T) -> Result<kdl::KdlDocument, Error> {
+	let Output::Map(fields) = value.serialize(Serializer)? else {
+		return Err(ser::Error::custom(
+			"only structs and maps can be serialized as a document",
+		));
+	};
+	let mut doc = builder::Node::default();
+	for (key, value) in fields {
+		value.push_to(&mut doc, key, false);
+	}
+	let mut doc = doc.into_document();
+	doc.autoformat();
+	Ok(doc)
+}
+
+/// The structure of a serialized value, before it has been written to a node.
+pub enum Output {
+	None,
+	Value(kdl::KdlValue),
+	Seq(Vec<Output>),
+	Map(Vec<(String, Output)>),
+	/// A non-unit enum variant, which is written as a child named after the variant.
+	Variant(&'static str, Box<Output>),
+}
+
+impl Output {
+	/// Converts the output into the contents of a node.
+	pub fn into_node(self) -> builder::Node {
+		let mut node = builder::Node::default();
+		match self {
+			Self::None => {}
+			Self::Value(value) => node.push(Value(value)),
+			Self::Seq(items) => {
+				let primitives = items.iter().all(|item| matches!(item, Self::None | Self::Value(_)));
+				for item in items {
+					match item {
+						Self::Value(value) => node.push(Value(value)),
+						Self::None if primitives => node.push(kdl::KdlEntry::new(kdl::KdlValue::Null)),
+						Self::None => {
+							let null = builder::Node::default().with(kdl::KdlEntry::new(kdl::KdlValue::Null));
+							node.push(Child("-", null));
+						}
+						// Elements which are not primitives are unnamed children
+						item => node.push(Child("-", item.into_node())),
+					}
+				}
+			}
+			Self::Map(fields) => {
+				for (key, value) in fields {
+					value.push_to(&mut node, key, true);
+				}
+			}
+			Self::Variant(name, inner) => node.push(Child(name, inner.into_node())),
+		}
+		node
+	}
+
+	/// Writes the output into a parent node as the field named `key`.
+	/// Primitive values are written as properties if `as_prop` is true, otherwise as a child with one argument.
+	fn push_to(self, parent: &mut builder::Node, key: String, as_prop: bool) {
+		match self {
+			Self::None => {}
+			Self::Value(value) if as_prop => parent.push(Property(key, Value(value))),
+			// Empty sequences are still written as an empty child, so that they are read back as empty.
+			Self::Seq(items) if !items.is_empty() && items.iter().all(|item| matches!(item, Self::Map(_))) => {
+				let nodes = items.into_iter().map(Self::into_node).collect::<Vec<_>>();
+				parent.push(Children(key, nodes));
+			}
+			output => parent.push(Child(key, output.into_node())),
+		}
+	}
+}
+
+pub struct Serializer;
+
+impl ser::Serializer for Serializer {
+	type Ok = Output;
+	type Error = Error;
+	type SerializeSeq = SerializeSeq;
+	type SerializeTuple = SerializeSeq;
+	type SerializeTupleStruct = SerializeSeq;
+	type SerializeTupleVariant = SerializeVariant<SerializeSeq>;
+	type SerializeMap = SerializeMap;
+	type SerializeStruct = SerializeMap;
+	type SerializeStructVariant = SerializeVariant<SerializeMap>;
+
+	fn serialize_bool(self, v: bool) -> Result<Output, Error> {
+		Ok(Output::Value(kdl::KdlValue::Bool(v)))
+	}
+
+	fn serialize_i8(self, v: i8) -> Result<Output, Error> {
+		self.serialize_i128(v.into())
+	}
+
+	fn serialize_i16(self, v: i16) -> Result<Output, Error> {
+		self.serialize_i128(v.into())
+	}
+
+	fn serialize_i32(self, v: i32) -> Result<Output, Error> {
+		self.serialize_i128(v.into())
+	}
+
+	fn serialize_i64(self, v: i64) -> Result<Output, Error> {
+		self.serialize_i128(v.into())
+	}
+
+	fn serialize_i128(self, v: i128) -> Result<Output, Error> {
+		Ok(Output::Value(kdl::KdlValue::Integer(v)))
+	}
+
+	fn serialize_u8(self, v: u8) -> Result<Output, Error> {
+		self.serialize_i128(v.into())
+	}
+
+	fn serialize_u16(self, v: u16) -> Result<Output, Error> {
+		self.serialize_i128(v.into())
+	}
+
+	fn serialize_u32(self, v: u32) -> Result<Output, Error> {
+		self.serialize_i128(v.into())
+	}
+
+	fn serialize_u64(self, v: u64) -> Result<Output, Error> {
+		self.serialize_i128(v.into())
+	}
+
+	fn serialize_u128(self, v: u128) -> Result<Output, Error> {
+		let v = i128::try_from(v).map_err(|_| ser::Error::custom(format!("{v} is too large for a KDL integer")))?;
+		self.serialize_i128(v)
+	}
+
+	fn serialize_f32(self, v: f32) -> Result<Output, Error> {
+		self.serialize_f64(v.into())
+	}
+
+	fn serialize_f64(self, v: f64) -> Result<Output, Error> {
+		Ok(Output::Value(kdl::KdlValue::Float(v)))
+	}
+
+	fn serialize_char(self, v: char) -> Result<Output, Error> {
+		self.serialize_str(v.encode_utf8(&mut [0; 4]))
+	}
+
+	fn serialize_str(self, v: &str) -> Result<Output, Error> {
+		Ok(Output::Value(kdl::KdlValue::String(v.to_owned())))
+	}
+
+	fn serialize_bytes(self, v: &[u8]) -> Result<Output, Error> {
+		let bytes = v
+			.iter()
+			.map(|byte| Output::Value(kdl::KdlValue::Integer((*byte).into())));
+		Ok(Output::Seq(bytes.collect()))
+	}
+
+	fn serialize_none(self) -> Result<Output, Error> {
+		Ok(Output::None)
+	}
+
+	fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Output, Error> {
+		value.serialize(self)
+	}
+
+	fn serialize_unit(self) -> Result<Output, Error> {
+		Ok(Output::None)
+	}
+
+	fn serialize_unit_struct(self, _name: &'static str) -> Result<Output, Error> {
+		Ok(Output::None)
+	}
+
+	fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<Output, Error> {
+		self.serialize_str(variant)
+	}
+
+	fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<Output, Error> {
+		value.serialize(self)
+	}
+
+	fn serialize_newtype_variant<T: Serialize + ?Sized>(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+		value: &T,
+	) -> Result<Output, Error> {
+		Ok(Output::Variant(variant, Box::new(value.serialize(self)?)))
+	}
+
+	fn serialize_seq(self, len: Option<usize>) -> Result<SerializeSeq, Error> {
+		Ok(SerializeSeq(Vec::with_capacity(len.unwrap_or_default())))
+	}
+
+	fn serialize_tuple(self, len: usize) -> Result<SerializeSeq, Error> {
+		self.serialize_seq(Some(len))
+	}
+
+	fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SerializeSeq, Error> {
+		self.serialize_seq(Some(len))
+	}
+
+	fn serialize_tuple_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+		len: usize,
+	) -> Result<SerializeVariant<SerializeSeq>, Error> {
+		Ok(SerializeVariant(variant, self.serialize_seq(Some(len))?))
+	}
+
+	fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap, Error> {
+		Ok(SerializeMap {
+			fields: Vec::with_capacity(len.unwrap_or_default()),
+			key: None,
+		})
+	}
+
+	fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeMap, Error> {
+		self.serialize_map(Some(len))
+	}
+
+	fn serialize_struct_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+		len: usize,
+	) -> Result<SerializeVariant<SerializeMap>, Error> {
+		Ok(SerializeVariant(variant, self.serialize_map(Some(len))?))
+	}
+}
+
+pub struct SerializeSeq(Vec<Output>);
+impl ser::SerializeSeq for SerializeSeq {
+	type Ok = Output;
+	type Error = Error;
+
+	fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+		self.0.push(value.serialize(Serializer)?);
+		Ok(())
+	}
+
+	fn end(self) -> Result<Output, Error> {
+		Ok(Output::Seq(self.0))
+	}
+}
+impl ser::SerializeTuple for SerializeSeq {
+	type Ok = Output;
+	type Error = Error;
+
+	fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+		ser::SerializeSeq::serialize_element(self, value)
+	}
+
+	fn end(self) -> Result<Output, Error> {
+		ser::SerializeSeq::end(self)
+	}
+}
+impl ser::SerializeTupleStruct for SerializeSeq {
+	type Ok = Output;
+	type Error = Error;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+		ser::SerializeSeq::serialize_element(self, value)
+	}
+
+	fn end(self) -> Result<Output, Error> {
+		ser::SerializeSeq::end(self)
+	}
+}
+
+pub struct SerializeMap {
+	fields: Vec<(String, Output)>,
+	key: Option<String>,
+}
+impl ser::SerializeMap for SerializeMap {
+	type Ok = Output;
+	type Error = Error;
+
+	fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+		let key = match key.serialize(Serializer)? {
+			Output::Value(kdl::KdlValue::String(key)) => key,
+			Output::Value(value @ (kdl::KdlValue::Integer(_) | kdl::KdlValue::Bool(_))) => value.to_string(),
+			_ => return Err(ser::Error::custom("map keys must be strings, integers or booleans")),
+		};
+		self.key = Some(key);
+		Ok(())
+	}
+
+	fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+		let key = self
+			.key
+			.take()
+			.ok_or_else(|| ser::Error::custom("value serialized before key"))?;
+		self.fields.push((key, value.serialize(Serializer)?));
+		Ok(())
+	}
+
+	fn end(self) -> Result<Output, Error> {
+		Ok(Output::Map(self.fields))
+	}
+}
+impl ser::SerializeStruct for SerializeMap {
+	type Ok = Output;
+	type Error = Error;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+		self.fields.push((key.to_owned(), value.serialize(Serializer)?));
+		Ok(())
+	}
+
+	fn end(self) -> Result<Output, Error> {
+		ser::SerializeMap::end(self)
+	}
+}
+
+pub struct SerializeVariant<S>(&'static str, S);
+impl ser::SerializeTupleVariant for SerializeVariant<SerializeSeq> {
+	type Ok = Output;
+	type Error = Error;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+		ser::SerializeSeq::serialize_element(&mut self.1, value)
+	}
+
+	fn end(self) -> Result<Output, Error> {
+		Ok(Output::Variant(self.0, Box::new(ser::SerializeSeq::end(self.1)?)))
+	}
+}
+impl ser::SerializeStructVariant for SerializeVariant<SerializeMap> {
+	type Ok = Output;
+	type Error = Error;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+		ser::SerializeStruct::serialize_field(&mut self.1, key, value)
+	}
+
+	fn end(self) -> Result<Output, Error> {
+		Ok(Output::Variant(self.0, Box::new(ser::SerializeMap::end(self.1)?)))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use serde::{Deserialize, Serialize};
+
+	#[derive(Serialize, Deserialize, Debug, PartialEq)]
+	struct Config {
+		name: String,
+		port: u16,
+		debug: Option<bool>,
+		tags: Vec<String>,
+		server: Vec<Server>,
+		mode: Mode,
+		shape: Shape,
+	}
+
+	#[derive(Serialize, Deserialize, Debug, PartialEq)]
+	struct Server {
+		host: String,
+		weight: Option<f32>,
+	}
+
+	#[derive(Serialize, Deserialize, Debug, PartialEq)]
+	#[serde(rename_all = "lowercase")]
+	enum Mode {
+		Fast,
+		Safe,
+	}
+
+	#[derive(Serialize, Deserialize, Debug, PartialEq)]
+	#[serde(rename_all = "lowercase")]
+	enum Shape {
+		Circle(f64),
+		Rect { width: u32, height: u32 },
+	}
+
+	fn config() -> Config {
+		Config {
+			name: "example".into(),
+			port: 8080,
+			debug: None,
+			tags: vec!["a".into(), "b".into()],
+			server: vec![
+				Server {
+					host: "one".into(),
+					weight: Some(0.5),
+				},
+				Server {
+					host: "two".into(),
+					weight: None,
+				},
+			],
+			mode: Mode::Safe,
+			shape: Shape::Rect { width: 2, height: 3 },
+		}
+	}
+
+	#[test]
+	fn document() -> Result<(), Error> {
+		let doc = to_document(&config())?;
+		let expected = "name example\nport 8080\ntags a b\nserver host=one weight=0.5\nserver host=two\nmode safe\nshape {\n    rect width=2 height=3\n}\n";
+		assert_eq!(doc.to_string(), expected);
+		Ok(())
+	}
+
+	#[test]
+	fn node() -> Result<(), Error> {
+		let node = to_node(&Server {
+			host: "local".into(),
+			weight: Some(2.0),
+		})?;
+		assert_eq!(node.build("server").to_string(), "server host=local weight=2.0\n");
+		Ok(())
+	}
+
+	#[test]
+	fn round_trip() -> Result<(), crate::de::Error> {
+		let doc = to_document(&config()).expect("serializes");
+		let parsed = doc.to_string().parse::<kdl::KdlDocument>().expect("valid kdl");
+		assert_eq!(crate::de::from_document::<Config>(&parsed)?, config());
+		Ok(())
+	}
+
+	#[test]
+	fn newtype_variant() -> Result<(), Error> {
+		let node = to_node(&Shape::Circle(1.5))?;
+		assert_eq!(node.build("shape").to_string(), "shape {\n    circle 1.5\n}\n");
+		Ok(())
+	}
+
+	#[test]
+	fn optional_elements() -> Result<(), crate::de::Error> {
+		#[derive(Serialize, Deserialize, Debug, PartialEq)]
+		struct Slots {
+			ports: Vec<Option<u16>>,
+			servers: Vec<Option<Server>>,
+		}
+		let slots = Slots {
+			ports: vec![Some(80), None, Some(443)],
+			servers: vec![
+				None,
+				Some(Server {
+					host: "one".into(),
+					weight: None,
+				}),
+			],
+		};
+		let doc = to_document(&slots).expect("serializes");
+		let expected = "ports 80 #null 443
+servers {
+    - #null
+    - host=one
+}
+";
+		assert_eq!(doc.to_string(), expected);
+		let parsed = doc.to_string().parse::<kdl::KdlDocument>().expect("valid kdl");
+		assert_eq!(crate::de::from_document::<Slots>(&parsed)?, slots);
+		Ok(())
+	}
+
+	#[test]
+	fn single_element() -> Result<(), crate::de::Error> {
+		#[derive(Serialize, Deserialize, Debug, PartialEq)]
+		struct Leaf {
+			v: u32,
+		}
+		#[derive(Serialize, Deserialize, Debug, PartialEq)]
+		struct Item {
+			leaf: Leaf,
+		}
+		#[derive(Serialize, Deserialize, Debug, PartialEq)]
+		struct Items {
+			items: Vec<Item>,
+		}
+		let items = Items {
+			items: vec![Item { leaf: Leaf { v: 0 } }],
+		};
+		let doc = to_document(&items).expect("serializes");
+		assert_eq!(doc.to_string(), "items {\n    leaf v=0\n}\n");
+		let parsed = doc.to_string().parse::<kdl::KdlDocument>().expect("valid kdl");
+		assert_eq!(crate::de::from_document::<Items>(&parsed)?, items);
+		Ok(())
+	}
+
+	#[test]
+	fn not_a_document() {
+		assert!(to_document(&42).is_err());
+	}
+}
@@ -0,0 +1,83 @@
+//! Values for types of other crates which are written as strings in a well-known format.
+//! UUIDs and URLs are written with the type annotations reserved by KDL, `(uuid)` and `(url)`.
+
+#[cfg(feature = "uuid")]
+impl_kdlvalue_parse!(
+	uuid::Uuid,
+	"a UUID, e.g. 67e55044-10b1-426f-9247-bb680e5fe0c8",
+	Some("uuid")
+);
+
+#[cfg(feature = "url")]
+impl_kdlvalue_parse!(url::Url, "an absolute URL, e.g. https://kdl.dev/", Some("url"));
+
+#[cfg(feature = "semver")]
+impl_kdlvalue_parse!(semver::Version, "a semantic version, e.g. 1.2.3");
+#[cfg(feature = "semver")]
+impl_kdlvalue_parse!(semver::VersionReq, "a semantic version requirement, e.g. ^1.2");
+
+#[cfg(test)]
+mod test {
+	use crate::{
+		builder::{Node, Value},
+		reader::{EntryExt, FailedToParseValue},
+		AsKdlValue, FromKdlValue,
+	};
+
+	fn written<V: AsKdlValue>(value: V) -> String {
+		Node::default().with(Value(value)).build("node").to_string()
+	}
+
+	fn read<T>(src: &str) -> Result<T, FailedToParseValue>
+	where
+		T: for<'doc> FromKdlValue<'doc>,
+		for<'doc> miette::Report: From<<T as FromKdlValue<'doc>>::Error>,
+	{
+		let doc = src.parse::<kdl::KdlDocument>().expect("valid kdl");
+		doc.nodes()[0].entries()[0].to::<T>()
+	}
+
+	#[cfg(feature = "uuid")]
+	#[test]
+	fn uuid() {
+		let id = uuid::Uuid::from_u128(0x67e55044_10b1_426f_9247_bb680e5fe0c8);
+		assert_eq!(written(id), "node (uuid)\"67e55044-10b1-426f-9247-bb680e5fe0c8\"\n");
+		assert_eq!(read("node (uuid)\"67e55044-10b1-426f-9247-bb680e5fe0c8\""), Ok(id));
+		assert!(read::<uuid::Uuid>("node (url)\"67e55044-10b1-426f-9247-bb680e5fe0c8\"").is_err());
+	}
+
+	#[cfg(feature = "url")]
+	#[test]
+	fn url() {
+		use crate::error::{InvalidString, MalformedValue};
+		let url = url::Url::parse("https://kdl.dev/spec").unwrap();
+		assert_eq!(written(&url), "node (url)\"https://kdl.dev/spec\"\n");
+		assert_eq!(read("node \"https://kdl.dev/spec\""), Ok(url));
+
+		// Errors are labeled at the entry which failed to parse.
+		let err = read::<url::Url>("node \"not a url\"").expect_err("relative url");
+		let label = miette::Diagnostic::labels(&err).and_then(|mut labels| labels.next());
+		assert_eq!(label.map(|label| label.offset()), Some(5));
+		let malformed = url::Url::from_kdl(&"not a url".into()).expect_err("relative url");
+		assert_eq!(
+			malformed,
+			InvalidString::Malformed(MalformedValue {
+				type_name: "url::Url",
+				value: "not a url".to_owned(),
+				format: "an absolute URL, e.g. https://kdl.dev/",
+				reason: "relative URL without a base".to_owned(),
+			})
+		);
+	}
+
+	#[cfg(feature = "semver")]
+	#[test]
+	fn semver() {
+		let version = semver::Version::new(1, 2, 3);
+		assert_eq!(written(&version), "node \"1.2.3\"\n");
+		assert_eq!(read("node \"1.2.3\""), Ok(version));
+		let req = read::<semver::VersionReq>("node \"^1.2\"").expect("valid requirement");
+		assert!(req.matches(&semver::Version::new(1, 4, 0)));
+		assert!(read::<semver::Version>("node \"1.2\"").is_err());
+	}
+}
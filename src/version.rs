@@ -0,0 +1,169 @@
+//! Selection of the KDL syntax version used to parse and write documents.
+//! Documents are always held as `kdl` v2 structures; KDL v1 syntax is supported via the `v1` feature.
+
+/// A version of the KDL syntax.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Version {
+	#[cfg(feature = "v1")]
+	V1,
+	V2,
+}
+
+impl Version {
+	/// Parses a document written in this version of the syntax.
+	pub fn parse(self, src: &str) -> Result<kdl::KdlDocument, kdl::KdlError> {
+		match self {
+			#[cfg(feature = "v1")]
+			Self::V1 => {
+				let mut doc = kdl::KdlDocument::parse_v1(src)?;
+				move_trailing_comments(&mut doc);
+				Ok(doc)
+			}
+			Self::V2 => kdl::KdlDocument::parse_v2(src),
+		}
+	}
+
+	/// Parses a document written in either version of the syntax, returning the version it was written in.
+	/// Documents which are valid in both versions are parsed as v2. If the document is invalid in both,
+	/// the v2 error is returned.
+	pub fn detect(src: &str) -> Result<(Self, kdl::KdlDocument), kdl::KdlError> {
+		let v2_err = match Self::V2.parse(src) {
+			Ok(doc) => return Ok((Self::V2, doc)),
+			Err(err) => err,
+		};
+		#[cfg(feature = "v1")]
+		if let Ok(doc) = Self::V1.parse(src) {
+			return Ok((Self::V1, doc));
+		}
+		Err(v2_err)
+	}
+
+	/// Converts a document (e.g. one made by the builder, or parsed from another version)
+	/// so that it is written in this version of the syntax. Comments are kept.
+	pub fn convert(self, doc: &mut kdl::KdlDocument) {
+		match self {
+			#[cfg(feature = "v1")]
+			Self::V1 => doc.ensure_v1(),
+			Self::V2 => doc.ensure_v2(),
+		}
+	}
+}
+
+/// Nodes parsed from v1 keep the comment at the end of their line after their newline terminator,
+/// which would move it onto the next line (or into the closing brace), so it is moved before the terminator.
+#[cfg(feature = "v1")]
+fn move_trailing_comments(doc: &mut kdl::KdlDocument) {
+	for node in doc.nodes_mut() {
+		if let Some(format) = node.format_mut() {
+			if format.terminator == "\n" && !format.trailing.is_empty() {
+				let trailing = std::mem::take(&mut format.trailing);
+				format.before_terminator.push_str(&trailing);
+			}
+		}
+		if let Some(children) = node.children_mut() {
+			move_trailing_comments(children);
+		}
+	}
+}
+
+/// Upgrades a document written in KDL v1 to KDL v2, keeping its comments and formatting.
+#[cfg(feature = "v1")]
+pub fn v1_to_v2(src: &str) -> Result<String, kdl::KdlError> {
+	let mut doc = Version::V1.parse(src)?;
+	Version::V2.convert(&mut doc);
+	Ok(doc.to_string())
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn detect_v2() {
+		let (version, doc) = Version::detect("node #true key=#null").expect("valid kdl");
+		assert_eq!(version, Version::V2);
+		assert_eq!(doc.nodes()[0].entries().len(), 2);
+	}
+
+	#[cfg(feature = "v1")]
+	#[test]
+	fn detect_v1() {
+		let (version, doc) = Version::detect("node true key=null \"a\"").expect("valid kdl");
+		assert_eq!(version, Version::V1);
+		let values = doc.nodes()[0]
+			.entries()
+			.iter()
+			.map(|entry| entry.value().clone())
+			.collect::<Vec<_>>();
+		assert_eq!(values, [true.into(), kdl::KdlValue::Null, "a".into()]);
+	}
+
+	#[cfg(feature = "v1")]
+	#[test]
+	fn upgrade_keeps_comments() {
+		let src = "// the server\nserver \"localhost\" tls=true /* for now */ {\n    port 80 // http\n}\n";
+		let expected = "// the server\nserver localhost tls=#true /* for now */ {\n    port 80 // http\n}\n";
+		assert_eq!(v1_to_v2(src).expect("valid kdl"), expected);
+	}
+
+	#[cfg(feature = "v1")]
+	#[test]
+	fn write_v1() {
+		use crate::builder::{Child, Node, Property, Value};
+		let node = Node::default().with(Child(
+			"server",
+			Node::default()
+				.with(Value("localhost"))
+				.with(Property("tls", Value(true)))
+				.with(Child("port", Value(80))),
+		));
+		let mut doc = node.into_document();
+		doc.autoformat();
+		Version::V1.convert(&mut doc);
+		let text = doc.to_string();
+		assert_eq!(text, "server \"localhost\" tls=true {\n    port 80\n}\n");
+		assert!(Version::V1.parse(&text).is_ok());
+	}
+
+	#[cfg(feature = "v1")]
+	#[test]
+	fn load_either_version() -> Result<(), crate::error::LoadError> {
+		use crate::{
+			builder::{Child, Node, Value},
+			reader::EntryExt,
+		};
+		#[derive(PartialEq, Debug)]
+		struct Config {
+			title: String,
+		}
+		impl<'doc> crate::FromKdlNode<'doc, ()> for Config {
+			type Error = crate::error::QueryError;
+			fn from_kdl(node: &mut crate::reader::Node<'doc, ()>) -> Result<Self, Self::Error> {
+				let title = node.child("title")?.next()?.to::<String>()?;
+				Ok(Self { title })
+			}
+		}
+		impl crate::AsKdlNode for Config {
+			fn as_kdl(&self) -> Node {
+				Node::default().with(Child("title", Value(&self.title)))
+			}
+		}
+		let config = Config { title: "app".into() };
+
+		let src = crate::to_string_version(&config, Version::V1);
+		assert_eq!(src, "title \"app\"\n");
+		assert_eq!(
+			crate::from_str_version::<Config, _>(&src, Some(Version::V1), &())?,
+			config
+		);
+		assert_eq!(crate::from_str_version::<Config, _>(&src, None, &())?, config);
+
+		let path = std::env::temp_dir().join(format!("kdlize-version-{}.kdl", std::process::id()));
+		std::fs::write(&path, &src).expect("writes file");
+		let loaded = crate::from_file_version::<Config, _>(&path, Some(Version::V1), &());
+		std::fs::remove_file(&path).expect("removes file");
+		assert_eq!(loaded?, config);
+		assert!(crate::from_str::<Config, _>("title \"app\" true", &()).is_err());
+		Ok(())
+	}
+}